@@ -0,0 +1,142 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::collections::VecDeque;
+
+use repr::mir;
+
+use crate::linear::{LinearCtx, LinearPlaces, LinearState, Violation};
+
+impl LinearCtx<'_> {
+    /// Runs the analysis over the whole function until the state at the entry of every basic
+    /// block reaches a fixpoint, then walks each basic block once more to collect violations.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if a statement of the function cannot be analyzed, since the states
+    /// which follow it would be wrong.
+    ///
+    pub(crate) fn check_body(
+        &self,
+        body: &mir::Body,
+        linear_places: &LinearPlaces,
+    ) -> anyhow::Result<Vec<Violation>> {
+        let mut entry_states = self.solve(body, linear_places)?;
+        let mut violations = vec![];
+
+        for (bb, bb_data) in body.basic_blocks.iter() {
+            let bb: mir::BasicBlock = bb.into();
+
+            let Some(mut state) = entry_states[bb.get_id()].take() else {
+                continue;
+            };
+
+            self.process_bb(body, linear_places, &mut state, bb_data, &mut violations)?;
+        }
+
+        Ok(violations)
+    }
+
+    /// Returns the state at the entry of every basic block once they reach a fixpoint, which
+    /// is [`None`] for the unreachable basic blocks.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if a statement of the function cannot be analyzed.
+    ///
+    pub(crate) fn solve(
+        &self,
+        body: &mir::Body,
+        linear_places: &LinearPlaces,
+    ) -> anyhow::Result<Vec<Option<LinearState>>> {
+        let Some((start_bb, _)) = body.basic_blocks.iter().next() else {
+            return Ok(vec![]);
+        };
+        let start_bb: mir::BasicBlock = start_bb.into();
+
+        let mut entry_states: Vec<Option<LinearState>> = vec![None; body.basic_blocks.len()];
+        entry_states[start_bb.get_id()] = Some(linear_places.entry_state());
+
+        let mut is_queued = vec![false; body.basic_blocks.len()];
+        is_queued[start_bb.get_id()] = true;
+
+        let mut worklist = VecDeque::from([start_bb]);
+
+        while let Some(bb) = worklist.pop_front() {
+            is_queued[bb.get_id()] = false;
+
+            let Some(mut state) = entry_states[bb.get_id()].clone() else {
+                continue;
+            };
+
+            let bb_data = &body.basic_blocks[bb.into_inner()];
+
+            // Violations are only collected once the fixpoint is reached.
+            self.process_bb(body, linear_places, &mut state, bb_data, &mut vec![])?;
+
+            // A pointer which is compared against null owns nothing on the null branch.
+            let null_branch = linear_places
+                .null_branch(body, bb_data)
+                .map(|(index, null_bb)| {
+                    let mut null_state = state.clone();
+                    linear_places.refine_null(&mut null_state, index);
+
+                    (null_bb, null_state)
+                });
+
+            for successor in successors(bb_data) {
+                let successor_state = match &null_branch {
+                    Some((null_bb, null_state)) if null_bb.get_id() == successor.get_id() => {
+                        null_state
+                    }
+                    _ => &state,
+                };
+
+                let entry_state = &mut entry_states[successor.get_id()];
+
+                let is_changed = match entry_state {
+                    Some(entry_state) => entry_state.join(successor_state),
+                    None => {
+                        *entry_state = Some(successor_state.clone());
+                        true
+                    }
+                };
+
+                if is_changed && !is_queued[successor.get_id()] {
+                    is_queued[successor.get_id()] = true;
+                    worklist.push_back(successor);
+                }
+            }
+        }
+
+        Ok(entry_states)
+    }
+
+    fn process_bb(
+        &self,
+        body: &mir::Body,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        bb_data: &mir::BasicBlockData,
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
+        for statement in &bb_data.statements {
+            self.process_statement(body, linear_places, state, statement, violations)?;
+        }
+
+        self.process_terminator(body, linear_places, state, &bb_data.terminator, violations);
+
+        Ok(())
+    }
+}
+
+fn successors(bb_data: &mir::BasicBlockData) -> Vec<mir::BasicBlock> {
+    match bb_data
+        .terminator
+        .as_ref()
+        .map(|terminator| &terminator.kind)
+    {
+        Some(mir::TerminatorKind::Goto { bb }) => vec![*bb],
+        Some(mir::TerminatorKind::SwitchInt { targets, .. }) => targets.to_vec(),
+        Some(mir::TerminatorKind::Return | mir::TerminatorKind::Unreachable) | None => vec![],
+    }
+}
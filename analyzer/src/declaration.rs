@@ -0,0 +1,179 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::collections::HashMap;
+
+use azhdaha_errors::Diagnostic;
+
+use repr::hir::{
+    self,
+    resolver::{Resolver, SymbolKind},
+};
+
+use crate::linear::{LinearCtx, Violation, ViolationKind};
+
+/// The signatures of the functions with external linkage which are defined in the program
+/// along with the path of the source file which defines each of them.
+#[derive(Debug, Default)]
+pub struct FuncDefs<'hir> {
+    defs: HashMap<&'hir str, (&'hir str, &'hir hir::FuncSig)>,
+}
+
+impl<'hir> FuncDefs<'hir> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects the definitions of the functions in the items of a translation unit.
+    pub fn collect(
+        &mut self,
+        source_path: &'hir str,
+        items: &'hir [hir::Item],
+        symbol_resolver: &'hir Resolver<SymbolKind>,
+    ) {
+        for item in items {
+            let hir::ItemKind::Func(func_def) = &item.kind else {
+                continue;
+            };
+
+            let SymbolKind::Func(func_decl) = symbol_resolver.get_data_by_res(&func_def.symbol)
+            else {
+                continue;
+            };
+
+            if matches!(func_decl.storage, Some(hir::Storage::Static)) {
+                continue;
+            }
+
+            self.defs
+                .insert(&func_decl.ident.name, (source_path, &func_decl.sig));
+        }
+    }
+}
+
+impl LinearCtx<'_> {
+    /// Reports the declarations of the functions in the translation unit which do not agree
+    /// with the definitions of the functions on the linearity of their parameters or their
+    /// returned value.
+    pub fn check_declarations(
+        &self,
+        items: &[hir::Item],
+        symbol_resolver: &Resolver<SymbolKind>,
+        func_defs: &FuncDefs,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for item in items {
+            let hir::ItemKind::Decl(symbols) = &item.kind else {
+                continue;
+            };
+
+            for symbol in symbols {
+                let SymbolKind::Var(var_decl) = symbol_resolver.get_data_by_res(symbol) else {
+                    continue;
+                };
+
+                let hir::TyKind::Func { sig: decl_sig } = &var_decl.ty.kind else {
+                    continue;
+                };
+
+                let Some((def_path, def_sig)) = func_defs.defs.get(var_decl.ident.name.as_str())
+                else {
+                    continue;
+                };
+
+                for violation in mismatches(&var_decl.ident.name, decl_sig, def_path, def_sig) {
+                    diagnostics.push(Diagnostic {
+                        function: Some(var_decl.ident.name.clone()),
+                        ..self.diagnose(&violation)
+                    });
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Compares the declaration against the signature of the definition.
+fn mismatches(
+    func_name: &str,
+    decl_sig: &hir::FuncSig,
+    def_path: &str,
+    def_sig: &hir::FuncSig,
+) -> Vec<Violation> {
+    let func_name = format!("`{func_name}`");
+
+    let mut violations = vec![];
+
+    if linearity_of(&decl_sig.ret_ty) != linearity_of(&def_sig.ret_ty) {
+        violations.push(
+            Violation::new(ViolationKind::MismatchedDeclaration, decl_sig.ret_ty.span)
+                .with_label(
+                    format!(
+                        "Function {func_name} is declared in here to return a {} value",
+                        linearity_of(&decl_sig.ret_ty)
+                    ),
+                    decl_sig.ret_ty.span,
+                )
+                .with_note(format!(
+                    "Function {func_name} is defined in \"{def_path}\" to return a {} value",
+                    linearity_of(&def_sig.ret_ty)
+                ))
+                .with_help("Try to annotate the declaration the same as the definition"),
+        );
+    }
+
+    // The parameters cannot be matched when the declaration is not a prototype.
+    if decl_sig.params.len() != def_sig.params.len() {
+        return violations;
+    }
+
+    for (position, (decl_param, def_param)) in decl_sig
+        .params
+        .iter()
+        .zip(def_sig.params.iter())
+        .enumerate()
+    {
+        if linearity_of(&decl_param.ty) == linearity_of(&def_param.ty) {
+            continue;
+        }
+
+        violations.push(
+            Violation::new(ViolationKind::MismatchedDeclaration, decl_param.span)
+                .with_label(
+                    format!(
+                        "Parameter {} is declared in here as {}",
+                        name_of(decl_param, position),
+                        linearity_of(&decl_param.ty)
+                    ),
+                    decl_param.span,
+                )
+                .with_note(format!(
+                    "Function {func_name} is defined in \"{def_path}\" where parameter {} is {}",
+                    name_of(def_param, position),
+                    linearity_of(&def_param.ty)
+                ))
+                .with_help("Try to annotate the declaration the same as the definition"),
+        );
+    }
+
+    violations
+}
+
+fn linearity_of(ty: &hir::Ty) -> &'static str {
+    if ty.is_linear {
+        "linear"
+    } else if ty.is_borrowed {
+        "borrowed"
+    } else {
+        "non-linear"
+    }
+}
+
+/// Returns the name of the parameter, or its position when it is unnamed.
+fn name_of(param_decl: &hir::ParamDecl, position: usize) -> String {
+    match &param_decl.ident {
+        Some(ident) => format!("`{}`", ident.name),
+        None => format!("#{}", position + 1),
+    }
+}
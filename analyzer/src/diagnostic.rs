@@ -0,0 +1,38 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use azhdaha_errors::{Diagnostic, Label, Location, Source as _, Stage};
+
+use repr::hir::Span;
+
+use crate::linear::{LinearCtx, Violation};
+
+impl LinearCtx<'_> {
+    pub(crate) fn diagnose(&self, violation: &Violation) -> Diagnostic {
+        Diagnostic {
+            severity: violation.kind.severity(),
+            stage: Stage::Analysis,
+            code: Some(violation.kind.code()),
+            kind: Some(violation.kind.name()),
+            message: violation.kind.message().to_owned(),
+            location: self.locate(violation.span),
+            function: None,
+            variable: None,
+            snippet: self.source_info.snippet(violation.span),
+            labels: violation
+                .labels
+                .iter()
+                .map(|(message, span)| Label {
+                    message: message.clone(),
+                    location: self.locate(*span),
+                })
+                .collect(),
+            notes: violation.notes.clone(),
+            helps: violation.helps.clone(),
+            function_line: None,
+        }
+    }
+
+    pub(crate) fn locate(&self, span: Span) -> Location {
+        self.source_info.location(span)
+    }
+}
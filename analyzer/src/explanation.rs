@@ -0,0 +1,72 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use crate::linear::ViolationKind;
+
+impl ViolationKind {
+    /// Returns the long-form explanation of the violation along with an erroneous and a fixed
+    /// example.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ViolationKind::MemoryLeakage => include_str!("../explanations/AZ0001.md"),
+            ViolationKind::MemoryLeakageAtExit => include_str!("../explanations/AZ0002.md"),
+            ViolationKind::DroppedOwnedField => include_str!("../explanations/AZ0003.md"),
+            ViolationKind::IgnoredLinearReturn => include_str!("../explanations/AZ0004.md"),
+            ViolationKind::UseOfMovedValue => include_str!("../explanations/AZ0005.md"),
+            ViolationKind::OverwritingOwnedValue => include_str!("../explanations/AZ0006.md"),
+            ViolationKind::NonLinearAssignment => include_str!("../explanations/AZ0007.md"),
+            ViolationKind::NonLinearArgument => include_str!("../explanations/AZ0008.md"),
+            ViolationKind::IgnoredLinearResult => include_str!("../explanations/AZ0009.md"),
+            ViolationKind::NonLinearResultStored => include_str!("../explanations/AZ0010.md"),
+            ViolationKind::LinearResultStored => include_str!("../explanations/AZ0011.md"),
+            ViolationKind::MismatchedDeclaration => include_str!("../explanations/AZ0012.md"),
+            ViolationKind::UnusedSuppression => include_str!("../explanations/AZ0013.md"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::linear::ViolationKind;
+
+    #[test]
+    fn every_code_has_an_explanation() {
+        let explanations = Path::new(env!("CARGO_MANIFEST_DIR")).join("explanations");
+
+        for kind in ViolationKind::ALL {
+            let path = explanations.join(format!("{}.md", kind.code()));
+
+            let explanation = fs::read_to_string(&path)
+                .unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+
+            assert_eq!(kind.explanation(), explanation, "{}", kind.code());
+        }
+
+        for entry in fs::read_dir(&explanations).unwrap() {
+            let path = entry.unwrap().path();
+            let code = path.file_stem().unwrap().to_str().unwrap();
+
+            assert!(
+                ViolationKind::from_code(code).is_some(),
+                "{} does not explain a known code",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn explains_only_the_known_codes() {
+        for kind in ViolationKind::ALL {
+            assert_eq!(ViolationKind::from_code(kind.code()), Some(kind));
+            assert_eq!(
+                ViolationKind::from_code(&kind.code().to_lowercase()),
+                Some(kind)
+            );
+        }
+
+        for code in ["", "AZ0000", "AZ0014", "AZ00011", "memory_leakage"] {
+            assert_eq!(ViolationKind::from_code(code), None, "{code}");
+        }
+    }
+}
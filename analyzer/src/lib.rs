@@ -2,20 +2,32 @@
 //! input source code and reports the possible memory leakages.
 //!
 
-use ariadne::Color;
-
-/// Contains methods needed to perform DFS on the MIR.
-mod dfs;
+/// Contains the worklist dataflow engine which runs the analysis over the MIR.
+mod dataflow;
+/// Contains methods needed to check the declarations of functions against their definitions.
+mod declaration;
+/// Contains the conversion of the violations into diagnostics.
+mod diagnostic;
+/// Contains the long-form explanations of the diagnostics.
+mod explanation;
 /// Contains linear datatypes' definitions.
 mod linear;
-/// Contains custom implementation of [`ariadne::Cache`] and [`ariadne::Span`].
-mod report;
+/// Contains methods needed to refine the state on the branches of null checks.
+mod nullness;
+/// Contains the query of the ownership of the linear variables at a point of a function.
+mod ownership;
+/// Contains methods needed to track MIR's [`repr::mir::Place`] paths.
+mod place;
 /// Contains methods needed to process MIR's [`repr::mir::Statement`].
 mod statement;
+/// Contains the inference of the functions' ownership summaries.
+mod summary;
+/// Contains the suppression of diagnostics through the comments in the source code.
+mod suppression;
 /// Contains methods needed to process MIR's [`repr::mir::Terminator`].
 mod terminator;
 
-pub use linear::LinearCtx;
-
-/// The color used to generate reports.
-pub(crate) const DIAGNOSIS_REPORT_COLOR: Color = Color::Rgb(255, 165, 0);
+pub use declaration::FuncDefs;
+pub use linear::{LinearCtx, LinearStatus, ViolationKind};
+pub use ownership::Ownership;
+pub use summary::FuncSummary;
@@ -1,126 +1,511 @@
 #![allow(clippy::missing_docs_in_private_items)]
 
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Index, IndexMut},
+};
+
 use anyhow::Context;
-use ariadne::Source;
-use la_arena::{Idx, RawIdx};
+use ast_utils::SourceInfo;
+use azhdaha_errors::{Diagnostic, Rule, Severity, Stage};
 use log::error;
 
-use repr::{hir::Span, mir};
+use repr::{
+    hir::Span,
+    mir::{self, RETURN_LOCAL},
+};
+
+use crate::summary::FuncSummary;
+
+/// A projection of a place which can be followed statically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum PathElem {
+    Field(usize),
+    Deref,
+}
 
-use crate::report::ReportCache;
+/// A local along with a chain of projections which identifies a tracked place.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PlacePath {
+    pub(crate) local: mir::Local,
+    pub(crate) projections: Vec<PathElem>,
+}
 
+/// A linear variable, or a linear field reachable from a variable, which its ownership is
+/// tracked by the analyzer.
 #[derive(Debug, Clone)]
-pub(crate) struct LinearLocal {
+pub(crate) struct LinearPlace {
     pub(crate) name: String,
-    pub(crate) local: mir::Local,
-    pub(crate) status: LinearStatus,
-    pub(crate) is_altered: bool,
+    pub(crate) path: PlacePath,
+    pub(crate) is_arg: bool,
     pub(crate) span: Span,
 }
 
-impl LinearLocal {
-    pub(crate) fn set_free(&mut self) {
-        if matches!(self.status, LinearStatus::Owner) {
-            self.is_altered = true;
+impl LinearPlace {
+    pub(crate) fn quoted_name(&self) -> String {
+        format!("`{}`", self.name)
+    }
+
+    pub(crate) fn is_field(&self) -> bool {
+        !self.path.projections.is_empty()
+    }
+
+    pub(crate) fn is_return(&self) -> bool {
+        self.path.local == RETURN_LOCAL && !self.is_field()
+    }
+
+    /// Whether the place lives in the function's own frame and dies when it returns.
+    pub(crate) fn is_in_frame(&self) -> bool {
+        !self.path.projections.contains(&PathElem::Deref)
+    }
+}
+
+/// All the linear places of a function along with a lookup table from their paths.
+#[derive(Debug, Clone)]
+pub(crate) struct LinearPlaces {
+    pub(crate) places: Vec<LinearPlace>,
+    pub(crate) indices: HashMap<PlacePath, usize>,
+    /// The tracked places which temporary values passed to functions are copied from.
+    pub(crate) arg_sources: HashMap<mir::Local, usize>,
+    /// The temporary values which own the result of a function inferred to return an owned
+    /// value.
+    pub(crate) owned_temps: HashSet<mir::Local>,
+}
+
+impl Index<usize> for LinearPlaces {
+    type Output = LinearPlace;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.places[index]
+    }
+}
+
+/// The status of a linear variable which forms a lattice where [`LinearStatus::Unknown`] is
+/// the bottom and [`LinearStatus::MaybeOwned`] is the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinearStatus {
+    Owner,
+    Free,
+    MaybeOwned,
+    Unknown,
+}
+
+impl LinearStatus {
+    /// Describes the ownership of a linear variable which has the status.
+    pub fn description(self) -> &'static str {
+        match self {
+            LinearStatus::Owner => "owns its value",
+            LinearStatus::Free => "does not own a value, since it is moved or freed",
+            LinearStatus::MaybeOwned => {
+                "owns its value only on some of the paths reaching this point"
+            }
+            LinearStatus::Unknown => "is not known to own a value, such as before it is assigned",
+        }
+    }
+
+    pub(crate) fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (LinearStatus::Unknown, status) | (status, LinearStatus::Unknown) => status,
+            (LinearStatus::Owner, LinearStatus::Owner) => LinearStatus::Owner,
+            (LinearStatus::Free, LinearStatus::Free) => LinearStatus::Free,
+            _ => LinearStatus::MaybeOwned,
         }
+    }
+}
 
+/// The status of a linear variable at a program point along with the labels collected on the
+/// paths reaching it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LinearValue {
+    pub(crate) status: LinearStatus,
+    pub(crate) labels: Vec<(String, Span)>,
+}
+
+impl LinearValue {
+    pub(crate) fn set_free(&mut self) {
         self.status = LinearStatus::Free;
     }
 
     pub(crate) fn set_owner(&mut self) {
-        if matches!(self.status, LinearStatus::Free) {
-            self.is_altered = true;
+        self.status = LinearStatus::Owner;
+    }
+
+    /// Forgets the status after a violation so the same mistake is not reported again on
+    /// the rest of the path.
+    pub(crate) fn reset(&mut self) {
+        self.status = LinearStatus::Unknown;
+    }
+
+    pub(crate) fn add_label(&mut self, message: String, span: Span) {
+        let label = (message, span);
+
+        if !self.labels.contains(&label) {
+            self.labels.push(label);
         }
+    }
 
-        self.status = LinearStatus::Owner;
+    fn join(&mut self, other: &Self) -> bool {
+        let status = self.status.join(other.status);
+        let mut is_changed = status != self.status;
+
+        self.status = status;
+
+        for label in &other.labels {
+            if !self.labels.contains(label) {
+                self.labels.push(label.clone());
+                is_changed = true;
+            }
+        }
+
+        is_changed
+    }
+}
+
+/// The state of all linear variables of a function at a program point.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LinearState(pub(crate) Vec<LinearValue>);
+
+impl LinearState {
+    /// Merges the other state into this one and returns whether anything has changed.
+    pub(crate) fn join(&mut self, other: &Self) -> bool {
+        self.0
+            .iter_mut()
+            .zip(other.0.iter())
+            .fold(false, |is_changed, (value, other_value)| {
+                value.join(other_value) | is_changed
+            })
+    }
+}
+
+impl Index<usize> for LinearState {
+    type Output = LinearValue;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for LinearState {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+/// The kinds of the violations of the linear rules which the analyzer reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    MemoryLeakage,
+    MemoryLeakageAtExit,
+    DroppedOwnedField,
+    IgnoredLinearReturn,
+    UseOfMovedValue,
+    OverwritingOwnedValue,
+    NonLinearAssignment,
+    NonLinearArgument,
+    IgnoredLinearResult,
+    NonLinearResultStored,
+    LinearResultStored,
+    MismatchedDeclaration,
+    UnusedSuppression,
+}
+
+impl ViolationKind {
+    /// All the kinds of violations in the order which their rules are listed in.
+    pub const ALL: [ViolationKind; 13] = [
+        ViolationKind::MemoryLeakage,
+        ViolationKind::MemoryLeakageAtExit,
+        ViolationKind::DroppedOwnedField,
+        ViolationKind::IgnoredLinearReturn,
+        ViolationKind::UseOfMovedValue,
+        ViolationKind::OverwritingOwnedValue,
+        ViolationKind::NonLinearAssignment,
+        ViolationKind::NonLinearArgument,
+        ViolationKind::IgnoredLinearResult,
+        ViolationKind::NonLinearResultStored,
+        ViolationKind::LinearResultStored,
+        ViolationKind::MismatchedDeclaration,
+        ViolationKind::UnusedSuppression,
+    ];
+
+    /// The code which identifies the kind of violation, which stays the same between releases.
+    pub fn code(self) -> &'static str {
+        match self {
+            ViolationKind::MemoryLeakage => "AZ0001",
+            ViolationKind::MemoryLeakageAtExit => "AZ0002",
+            ViolationKind::DroppedOwnedField => "AZ0003",
+            ViolationKind::IgnoredLinearReturn => "AZ0004",
+            ViolationKind::UseOfMovedValue => "AZ0005",
+            ViolationKind::OverwritingOwnedValue => "AZ0006",
+            ViolationKind::NonLinearAssignment => "AZ0007",
+            ViolationKind::NonLinearArgument => "AZ0008",
+            ViolationKind::IgnoredLinearResult => "AZ0009",
+            ViolationKind::NonLinearResultStored => "AZ0010",
+            ViolationKind::LinearResultStored => "AZ0011",
+            ViolationKind::MismatchedDeclaration => "AZ0012",
+            ViolationKind::UnusedSuppression => "AZ0013",
+        }
+    }
+
+    /// Returns the kind of violation which is identified by the code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.code().eq_ignore_ascii_case(code))
+    }
+
+    /// The name of the kind of violation, which the diagnostics were identified by before
+    /// they had codes.
+    pub fn name(self) -> &'static str {
+        match self {
+            ViolationKind::MemoryLeakage => "memory_leakage",
+            ViolationKind::MemoryLeakageAtExit => "memory_leakage_at_exit",
+            ViolationKind::DroppedOwnedField => "dropped_owned_field",
+            ViolationKind::IgnoredLinearReturn => "ignored_linear_return",
+            ViolationKind::UseOfMovedValue => "use_of_moved_value",
+            ViolationKind::OverwritingOwnedValue => "overwriting_owned_value",
+            ViolationKind::NonLinearAssignment => "non_linear_assignment",
+            ViolationKind::NonLinearArgument => "non_linear_argument",
+            ViolationKind::IgnoredLinearResult => "ignored_linear_result",
+            ViolationKind::NonLinearResultStored => "non_linear_result_stored",
+            ViolationKind::LinearResultStored => "linear_result_stored",
+            ViolationKind::MismatchedDeclaration => "mismatched_declaration",
+            ViolationKind::UnusedSuppression => "unused_suppression",
+        }
+    }
+
+    /// The name of the rule which is violated, which stays the same between releases.
+    pub fn rule_id(self) -> &'static str {
+        match self {
+            ViolationKind::MemoryLeakage => "memory-leakage",
+            ViolationKind::MemoryLeakageAtExit => "memory-leakage-at-exit",
+            ViolationKind::DroppedOwnedField => "dropped-owned-field",
+            ViolationKind::IgnoredLinearReturn => "ignored-linear-return",
+            ViolationKind::UseOfMovedValue => "use-of-moved-value",
+            ViolationKind::OverwritingOwnedValue => "overwriting-owned-value",
+            ViolationKind::NonLinearAssignment => "non-linear-assignment",
+            ViolationKind::NonLinearArgument => "non-linear-argument",
+            ViolationKind::IgnoredLinearResult => "ignored-linear-result",
+            ViolationKind::NonLinearResultStored => "non-linear-result-stored",
+            ViolationKind::LinearResultStored => "linear-result-stored",
+            ViolationKind::MismatchedDeclaration => "mismatched-declaration",
+            ViolationKind::UnusedSuppression => "unused-suppression",
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            ViolationKind::MemoryLeakage => "Memory leakage after return",
+            ViolationKind::MemoryLeakageAtExit => "Memory leakage before exiting the program",
+            ViolationKind::DroppedOwnedField => {
+                "Memory leakage after dropping the owner of a field"
+            }
+            ViolationKind::IgnoredLinearReturn => "Ignored linear return type",
+            ViolationKind::UseOfMovedValue => "Use of moved value",
+            ViolationKind::OverwritingOwnedValue => "Overwriting owned value",
+            ViolationKind::NonLinearAssignment => "Assignment of non-linear to linear",
+            ViolationKind::NonLinearArgument => "Passed non-linear as linear in function call",
+            ViolationKind::IgnoredLinearResult => "Ignored linear result after function call",
+            ViolationKind::NonLinearResultStored => {
+                "Stored non-linear as linear after function call"
+            }
+            ViolationKind::LinearResultStored => "Stored linear as non-linear after function call",
+            ViolationKind::MismatchedDeclaration => {
+                "Mismatched linearity between declaration and definition"
+            }
+            ViolationKind::UnusedSuppression => "Unused suppression",
+        }
+    }
+
+    /// The severity of the violation, where the unused suppressions are only warned about
+    /// since they do not affect the program.
+    pub fn severity(self) -> Severity {
+        match self {
+            ViolationKind::UnusedSuppression => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// The rule which describes the violation in the SARIF log.
+    pub fn rule(self) -> Rule {
+        Rule {
+            id: self.code(),
+            name: self.rule_id(),
+            description: self.message(),
+            severity: self.severity(),
+        }
     }
 }
 
+/// A violation of the linear rules along with the location which it was detected at and
+/// the information needed to report it.
 #[derive(Debug, Clone)]
-pub(crate) enum LinearStatus {
-    Owner,
-    Free,
-    Unknown,
+pub(crate) struct Violation {
+    pub(crate) kind: ViolationKind,
+    pub(crate) span: Span,
+    pub(crate) place: Option<usize>,
+    pub(crate) labels: Vec<(String, Span)>,
+    pub(crate) notes: Vec<String>,
+    pub(crate) helps: Vec<String>,
+}
+
+impl Violation {
+    pub(crate) fn new(kind: ViolationKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            place: None,
+            labels: vec![],
+            notes: vec![],
+            helps: vec![],
+        }
+    }
+
+    /// Creates a violation caused by the given linear place which carries the labels
+    /// collected on the paths reaching it.
+    pub(crate) fn of_place(
+        kind: ViolationKind,
+        span: Span,
+        linear_places: &LinearPlaces,
+        index: usize,
+        linear_value: &LinearValue,
+    ) -> Self {
+        let linear_place = &linear_places[index];
+
+        let mut labels = vec![(
+            format!(
+                "{} {} is defined in here as linear",
+                if linear_place.is_field() {
+                    "Field"
+                } else {
+                    "Variable"
+                },
+                linear_place.quoted_name()
+            ),
+            linear_place.span,
+        )];
+
+        labels.extend(linear_value.labels.iter().cloned());
+
+        let mut violation = Self {
+            place: Some(index),
+            labels,
+            ..Self::new(kind, span)
+        };
+
+        if linear_value.status == LinearStatus::MaybeOwned {
+            violation.notes.push(format!(
+                "{}'s value is moved only on some of the paths reaching this point",
+                linear_place.quoted_name()
+            ));
+        }
+
+        violation
+    }
+
+    pub(crate) fn with_label(mut self, message: impl Into<String>, span: Span) -> Self {
+        self.labels.push((message.into(), span));
+        self
+    }
+
+    /// Labels the statement which stores a value in the target, along with the definition of
+    /// the target when it is a named variable rather than a temporary.
+    pub(crate) fn with_target_labels(
+        self,
+        target: Option<&(String, Span)>,
+        linearity: &str,
+        message: String,
+        span: Span,
+    ) -> Self {
+        match target {
+            Some((name, decl_span)) => self
+                .with_label(
+                    format!("Variable `{name}` is defined in here as {linearity}"),
+                    *decl_span,
+                )
+                .with_label(format!("{message} to `{name}`"), span),
+            None => self.with_label(message, span),
+        }
+    }
+
+    pub(crate) fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub(crate) fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.helps.push(help.into());
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct LinearCtx<'linear> {
-    pub(crate) source_path: String,
-    pub(crate) report_source: Source<&'linear str>,
+    pub(crate) source_info: &'linear SourceInfo,
+    pub(crate) source_code: &'linear str,
+    pub(crate) allow_exit_leaks: bool,
+    pub(crate) summaries: HashMap<String, FuncSummary>,
 }
 
 impl<'linear> LinearCtx<'linear> {
-    pub fn new(source_path: &str, source_code: &'linear [u8]) -> anyhow::Result<Self> {
+    pub fn new(source_info: &'linear SourceInfo, allow_exit_leaks: bool) -> anyhow::Result<Self> {
+        let source_code = str::from_utf8(&source_info.code)
+            .context("UTF-8 validity for the source code failed.")?;
+
         Ok(Self {
-            source_path: source_path.to_owned(),
-            report_source: Source::from(
-                str::from_utf8(source_code)
-                    .context("UTF-8 validity for the source code failed.")?,
-            ),
+            source_info,
+            source_code,
+            allow_exit_leaks,
+            summaries: HashMap::new(),
         })
     }
 
-    pub fn analyze(&self, body: &mir::Body) -> Option<Vec<u8>> {
-        let mut linear_locals: Vec<(LinearLocal, bool)> = body
-            .local_decls
-            .iter()
-            .filter_map(|(local, local_decl)| match &local_decl.kind {
-                mir::LocalKind::Real { ident, is_arg, .. } => {
-                    if !local_decl.ty.is_linear {
-                        return None;
-                    }
+    pub fn analyze(&self, body: &mir::Body) -> Vec<Diagnostic> {
+        let linear_places = LinearPlaces::new(body, &self.summaries);
 
-                    Some((
-                        LinearLocal {
-                            name: ident.name.clone(),
-                            local,
-                            status: LinearStatus::Unknown,
-                            is_altered: false,
-                            span: local_decl.span,
-                        },
-                        *is_arg,
-                    ))
-                }
-                mir::LocalKind::Temp => None,
-            })
-            .collect();
-
-        // TODO: Must be removed in the future.
-        linear_locals.push((
-            LinearLocal {
-                name: "dummy".to_owned(),
-                local: Idx::from_raw(RawIdx::from_u32(u32::MAX)),
-                status: LinearStatus::Free,
-                is_altered: false,
-                span: repr::hir::Span { lo: 0, hi: 0 },
-            },
-            false,
-        ));
-
-        for (linear_local, is_arg) in linear_locals {
-            for (bb, _) in body.basic_blocks.iter() {
-                let mut linear_local = linear_local.clone();
-
-                if bb.into_raw().into_u32() == 0 && is_arg {
-                    linear_local.status = LinearStatus::Owner;
-                }
-
-                if let Some(report) = self.dfs_with_stack(body, linear_local, bb.into()) {
-                    let mut result = vec![];
-
-                    match report.write_for_stdout(
-                        ReportCache::new(self.source_path.clone(), &self.report_source),
-                        &mut result,
-                    ) {
-                        Ok(()) => return Some(result),
-                        Err(error) => {
-                            error!("Failed to print the linear analyzer's report - {error:?}")
-                        }
+        // The returned value is declared with the identifier of the function.
+        let func_ident = match &body.local_decls[RETURN_LOCAL].kind {
+            mir::LocalKind::Real { ident, .. } => Some(ident),
+            mir::LocalKind::Temp => None,
+        };
+
+        let mut reported = HashSet::new();
+        let mut diagnostics = vec![];
+
+        let violations = match self.check_body(body, &linear_places) {
+            Ok(violations) => violations,
+            Err(err) => {
+                error!("Failed to analyze the function - {err:?}");
+
+                // The function whose states cannot reach a fixpoint is reported as a failure of
+                // the tool, since its violations would be incomplete.
+                return vec![
+                    azhdaha_errors::Error {
+                        primary_message: (
+                            format!("Failed to analyze the function - {err}"),
+                            body.span,
+                        ),
+                        additional_messages: vec![],
                     }
-                }
+                    .to_diagnostic(Stage::Dataflow, self.source_info),
+                ];
+            }
+        };
+
+        for violation in violations {
+            if !reported.insert((violation.kind, violation.span, violation.place)) {
+                continue;
             }
+
+            diagnostics.push(Diagnostic {
+                function: func_ident.map(|ident| ident.name.clone()),
+                variable: violation
+                    .place
+                    .map(|index| linear_places.places[index].name.clone()),
+                function_line: func_ident.map(|ident| self.locate(ident.span).line),
+                ..self.diagnose(&violation)
+            });
         }
 
-        None
+        diagnostics
     }
 }
@@ -0,0 +1,153 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use repr::{
+    hir::{self, resolver},
+    mir,
+};
+
+use crate::linear::{LinearPlaces, LinearState, PlacePath};
+
+/// The value of an operand in terms of whether a tracked place is null.
+#[derive(Debug, Clone, Copy)]
+enum Nullness {
+    /// The null pointer constant.
+    Null,
+    /// Non-zero exactly when the tracked place is not null.
+    NotNull(usize),
+    /// Non-zero exactly when the tracked place is null.
+    IsNull(usize),
+}
+
+impl Nullness {
+    fn negate(self) -> Option<Self> {
+        match self {
+            Nullness::Null => None,
+            Nullness::NotNull(index) => Some(Nullness::IsNull(index)),
+            Nullness::IsNull(index) => Some(Nullness::NotNull(index)),
+        }
+    }
+}
+
+impl LinearPlaces {
+    /// Returns the tracked place which is compared against null by the terminator of the
+    /// basic block along with the target which is taken when the place is null.
+    pub(crate) fn null_branch(
+        &self,
+        body: &mir::Body,
+        bb_data: &mir::BasicBlockData,
+    ) -> Option<(usize, mir::BasicBlock)> {
+        let mir::TerminatorKind::SwitchInt { discr, targets } = &bb_data.terminator.as_ref()?.kind
+        else {
+            return None;
+        };
+
+        if targets[0].get_id() == targets[1].get_id() {
+            return None;
+        }
+
+        let statements = &bb_data.statements;
+
+        match self.nullness_of(body, statements, statements.len(), discr)? {
+            Nullness::Null => None,
+            Nullness::NotNull(index) => Some((index, targets[1])),
+            Nullness::IsNull(index) => Some((index, targets[0])),
+        }
+    }
+
+    /// Forgets the value of the tracked place along with every place reached through it,
+    /// since a null pointer owns nothing.
+    pub(crate) fn refine_null(&self, state: &mut LinearState, index: usize) {
+        state[index].reset();
+
+        for descendant in self.descendants_of(&self[index].path) {
+            state[descendant].reset();
+        }
+    }
+
+    /// Follows the temporary values which the operand is computed from among the statements
+    /// before `end`.
+    fn nullness_of(
+        &self,
+        body: &mir::Body,
+        statements: &[mir::Statement],
+        end: usize,
+        operand: &mir::Operand,
+    ) -> Option<Nullness> {
+        let place = match operand {
+            mir::Operand::Place(place) => place,
+            mir::Operand::Const(constant) => {
+                return is_null_const(body, constant).then_some(Nullness::Null);
+            }
+        };
+
+        if let Some(index) = self.index_of(place) {
+            // The place must still hold the compared value when the branch is taken.
+            let is_reassigned = statements[end..].iter().any(|statement| {
+                matches!(
+                    &statement.kind,
+                    mir::StatementKind::Assign(lhs, _) if lhs.local == place.local
+                )
+            });
+
+            return (!is_reassigned).then_some(Nullness::NotNull(index));
+        }
+
+        if !place.projections.is_empty()
+            || !matches!(body.local_decls[place.local].kind, mir::LocalKind::Temp)
+        {
+            return None;
+        }
+
+        let position = statements[..end].iter().rposition(|statement| {
+            matches!(
+                &statement.kind,
+                mir::StatementKind::Assign(lhs, _)
+                    if PlacePath::from_place(lhs) == PlacePath::from_place(place)
+            )
+        })?;
+
+        let mir::StatementKind::Assign(_, rvalue) = &statements[position].kind else {
+            return None;
+        };
+
+        match rvalue {
+            mir::Rvalue::Use(operand) | mir::Rvalue::Cast { value: operand, .. } => {
+                self.nullness_of(body, statements, position, operand)
+            }
+            mir::Rvalue::UnaryOp(mir::IntUnOp::Not, operand) => self
+                .nullness_of(body, statements, position, operand)?
+                .negate(),
+            mir::Rvalue::BinaryOp(
+                bin_op @ (mir::IntBinOp::Eq | mir::IntBinOp::Ne),
+                left_operand,
+                right_operand,
+            ) => {
+                let compared = match (
+                    self.nullness_of(body, statements, position, left_operand)?,
+                    self.nullness_of(body, statements, position, right_operand)?,
+                ) {
+                    (Nullness::Null, compared) | (compared, Nullness::Null) => compared,
+                    _ => return None,
+                };
+
+                match bin_op {
+                    mir::IntBinOp::Eq => compared.negate(),
+                    _ => Some(compared),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether the constant is either `0` or `nullptr`, which `NULL` is also expanded to.
+fn is_null_const(body: &mir::Body, constant: &mir::Const) -> bool {
+    match constant {
+        mir::Const::Lit(lit) => matches!(lit.kind, hir::LitKind::Int(0)),
+        mir::Const::Symbol(symbol) => matches!(
+            body.symbol_resolver.get_data_by_res(symbol),
+            resolver::SymbolKind::Var(var_decl) if var_decl.ident.name == "nullptr"
+        ),
+        mir::Const::Sizeof(_) => false,
+    }
+}
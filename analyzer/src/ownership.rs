@@ -0,0 +1,102 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::collections::HashMap;
+
+use repr::{hir::Span, mir};
+
+use crate::linear::{LinearCtx, LinearPlaces, LinearState, LinearStatus};
+
+/// The status of the linear variables of a function right before each of its reachable
+/// statements, joined over all the paths reaching them.
+#[derive(Debug, Clone)]
+pub struct Ownership {
+    /// The span of the function.
+    span: Span,
+    /// The names of the linear variables.
+    names: Vec<String>,
+    /// The spans of the statements along with the state right before them.
+    states: HashMap<Span, LinearState>,
+}
+
+impl Ownership {
+    /// Whether the function contains the offset.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.span.lo <= offset && offset < self.span.hi
+    }
+
+    /// Returns the status of the linear variables right before the innermost statement which
+    /// contains the offset. It is empty when no reachable statement contains the offset.
+    pub fn at(&self, offset: usize) -> Vec<(String, LinearStatus)> {
+        let Some((_, state)) = self
+            .states
+            .iter()
+            .filter(|(span, _)| span.lo <= offset && offset < span.hi)
+            .min_by_key(|(span, _)| (span.hi - span.lo, span.lo))
+        else {
+            return vec![];
+        };
+
+        self.names
+            .iter()
+            .zip(&state.0)
+            .map(|(name, linear_value)| (name.clone(), linear_value.status))
+            .collect()
+    }
+}
+
+impl LinearCtx<'_> {
+    /// Returns the status of the linear variables of the function right before each of its
+    /// reachable statements, which are unknown when the function cannot be analyzed.
+    pub fn ownership(&self, body: &mir::Body) -> Ownership {
+        let linear_places = LinearPlaces::new(body, &self.summaries);
+        let entry_states = self.solve(body, &linear_places).unwrap_or_default();
+
+        let mut states = HashMap::new();
+
+        for (bb, bb_data) in body.basic_blocks.iter() {
+            let bb: mir::BasicBlock = bb.into();
+
+            let Some(mut state) = entry_states.get(bb.get_id()).cloned().flatten() else {
+                continue;
+            };
+
+            for statement in &bb_data.statements {
+                record(&mut states, statement.span, &state);
+
+                // Violations and errors are reported by the analysis itself.
+                let _ = self.process_statement(
+                    body,
+                    &linear_places,
+                    &mut state,
+                    statement,
+                    &mut vec![],
+                );
+            }
+
+            if let Some(terminator) = &bb_data.terminator {
+                record(&mut states, terminator.span, &state);
+            }
+        }
+
+        Ownership {
+            span: body.span,
+            names: linear_places
+                .places
+                .iter()
+                .map(|linear_place| linear_place.name.clone())
+                .collect(),
+            states,
+        }
+    }
+}
+
+/// Records the state right before the statement of the span, while joining the states of the
+/// same span which is reached from several basic blocks.
+fn record(states: &mut HashMap<Span, LinearState>, span: Span, state: &LinearState) {
+    states
+        .entry(span)
+        .and_modify(|other_state| {
+            other_state.join(state);
+        })
+        .or_insert_with(|| state.clone());
+}
@@ -0,0 +1,383 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::collections::{HashMap, HashSet};
+
+use repr::{
+    hir::Span,
+    mir::{self, RETURN_LOCAL},
+};
+
+use crate::{
+    linear::{
+        LinearPlace, LinearPlaces, LinearState, LinearStatus, LinearValue, PathElem, PlacePath,
+    },
+    summary::{FuncSummary, callee_name},
+};
+
+impl PlacePath {
+    pub(crate) fn root(local: mir::Local) -> Self {
+        Self {
+            local,
+            projections: vec![],
+        }
+    }
+
+    /// Returns the path of the place unless it has projections which cannot be followed
+    /// statically.
+    pub(crate) fn from_place(place: &mir::Place) -> Option<Self> {
+        let mut path = Self::root(place.local);
+
+        for projection in &place.projections {
+            path.projections.push(match projection {
+                mir::PlaceElem::Field(field_index) => PathElem::Field(*field_index),
+                mir::PlaceElem::Deref => PathElem::Deref,
+                mir::PlaceElem::Index(_) => return None,
+            });
+        }
+
+        Some(path)
+    }
+
+    /// Returns the paths which the place is reached through, from the shortest one.
+    fn prefixes_of(place: &mir::Place) -> Vec<Self> {
+        let mut prefixes = vec![];
+        let mut prefix = Self::root(place.local);
+
+        for projection in &place.projections {
+            prefixes.push(prefix.clone());
+
+            prefix.projections.push(match projection {
+                mir::PlaceElem::Field(field_index) => PathElem::Field(*field_index),
+                mir::PlaceElem::Deref => PathElem::Deref,
+                mir::PlaceElem::Index(_) => break,
+            });
+        }
+
+        prefixes
+    }
+
+    /// Whether the path is a field which is stored directly in the value of the other path,
+    /// without being reached through a pointer.
+    pub(crate) fn is_stored_in(&self, other: &Self) -> bool {
+        other.is_prefix_of(self)
+            && !self.projections[other.projections.len()..].contains(&PathElem::Deref)
+    }
+
+    fn is_prefix_of(&self, other: &Self) -> bool {
+        self.local == other.local
+            && self.projections.len() < other.projections.len()
+            && other.projections.starts_with(&self.projections)
+    }
+}
+
+impl LinearPlaces {
+    /// Collects the linear variables of the function along with every linear field which is
+    /// accessed through a variable in its body.
+    pub(crate) fn new(body: &mir::Body, summaries: &HashMap<String, FuncSummary>) -> Self {
+        Self::collect(body, summaries, false)
+    }
+
+    /// Collects the linear places of the function while also tracking its unannotated
+    /// pointer variables, parameters and returned value, in order to infer its summary.
+    pub(crate) fn for_summary(body: &mir::Body, summaries: &HashMap<String, FuncSummary>) -> Self {
+        Self::collect(body, summaries, true)
+    }
+
+    fn collect(
+        body: &mir::Body,
+        summaries: &HashMap<String, FuncSummary>,
+        track_pointers: bool,
+    ) -> Self {
+        let mut linear_places = Self {
+            places: vec![],
+            indices: HashMap::new(),
+            arg_sources: HashMap::new(),
+            owned_temps: HashSet::new(),
+        };
+
+        for (local, local_decl) in body.local_decls.iter() {
+            let is_arg = matches!(local_decl.kind, mir::LocalKind::Real { is_arg: true, .. });
+
+            // The linear fields stored in a compound value are its own obligations, except for
+            // the returned value which hands them to the caller.
+            if local != RETURN_LOCAL {
+                for field_path in local_decl.ty.kind.linear_fields(body.type_tag_resolver) {
+                    let path = PlacePath {
+                        local,
+                        projections: field_path.into_iter().map(PathElem::Field).collect(),
+                    };
+
+                    if let Some(linear_place) = resolve_linear_field(body, path) {
+                        linear_places.insert(LinearPlace {
+                            is_arg,
+                            ..linear_place
+                        });
+                    }
+                }
+            }
+
+            let mir::LocalKind::Real { ident, .. } = &local_decl.kind else {
+                continue;
+            };
+
+            let is_tracked_pointer = track_pointers
+                && !local_decl.ty.is_borrowed
+                && matches!(local_decl.ty.kind, repr::hir::TyKind::Ptr { .. });
+
+            if !local_decl.ty.is_linear && !is_tracked_pointer {
+                continue;
+            }
+
+            linear_places.insert(LinearPlace {
+                name: ident.name.clone(),
+                path: PlacePath::root(local),
+                is_arg,
+                span: local_decl.span,
+            });
+        }
+
+        for (_, bb_data) in body.basic_blocks.iter() {
+            for statement in &bb_data.statements {
+                let (lhs, operands) = match &statement.kind {
+                    mir::StatementKind::Assign(lhs, rvalue) => (
+                        Some(lhs),
+                        match rvalue {
+                            mir::Rvalue::Use(operand)
+                            | mir::Rvalue::Cast { value: operand, .. }
+                            | mir::Rvalue::UnaryOp(_, operand) => vec![operand],
+                            mir::Rvalue::BinaryOp(_, left_operand, right_operand)
+                            | mir::Rvalue::PtrDiff(left_operand, right_operand) => {
+                                vec![left_operand, right_operand]
+                            }
+                            mir::Rvalue::Call(_, params) => params.iter().collect(),
+                            mir::Rvalue::AddrOf(_)
+                            | mir::Rvalue::AddrOfStatic(_)
+                            | mir::Rvalue::CompoundInitializing(..)
+                            | mir::Rvalue::VaArg(..)
+                            | mir::Rvalue::Empty => vec![],
+                        },
+                    ),
+                    mir::StatementKind::Call(_, params) => (None, params.iter().collect()),
+                };
+
+                let places = operands.into_iter().filter_map(|operand| match operand {
+                    mir::Operand::Place(place) => Some(place),
+                    mir::Operand::Const(_) => None,
+                });
+
+                for place in lhs.into_iter().chain(places) {
+                    if let Some(linear_place) = PlacePath::from_place(place)
+                        .and_then(|path| resolve_linear_field(body, path))
+                    {
+                        linear_places.insert(linear_place);
+                    }
+                }
+            }
+        }
+
+        for (_, bb_data) in body.basic_blocks.iter() {
+            for statement in &bb_data.statements {
+                let mir::StatementKind::Assign(lhs, rvalue) = &statement.kind else {
+                    continue;
+                };
+
+                if !lhs.projections.is_empty()
+                    || !matches!(body.local_decls[lhs.local].kind, mir::LocalKind::Temp)
+                {
+                    continue;
+                }
+
+                match rvalue {
+                    mir::Rvalue::Use(mir::Operand::Place(place))
+                    | mir::Rvalue::Cast {
+                        value: mir::Operand::Place(place),
+                        ..
+                    } if let Some(index) = linear_places.index_of(place) => {
+                        linear_places.arg_sources.insert(lhs.local, index);
+                    }
+                    mir::Rvalue::Call(func, _)
+                        if callee_name(body, func)
+                            .and_then(|func_name| summaries.get(func_name))
+                            .is_some_and(|summary| summary.returns_owned) =>
+                    {
+                        linear_places.owned_temps.insert(lhs.local);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        linear_places
+    }
+
+    fn insert(&mut self, linear_place: LinearPlace) {
+        if self.indices.contains_key(&linear_place.path) {
+            return;
+        }
+
+        self.indices
+            .insert(linear_place.path.clone(), self.places.len());
+        self.places.push(linear_place);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, &LinearPlace)> {
+        self.places.iter().enumerate()
+    }
+
+    pub(crate) fn index_of(&self, place: &mir::Place) -> Option<usize> {
+        self.index_of_path(&PlacePath::from_place(place)?)
+    }
+
+    pub(crate) fn index_of_path(&self, path: &PlacePath) -> Option<usize> {
+        self.indices.get(path).copied()
+    }
+
+    /// Returns the tracked places which the place is reached through, from the shortest one.
+    pub(crate) fn prefixes_of(&self, place: &mir::Place) -> Vec<usize> {
+        PlacePath::prefixes_of(place)
+            .iter()
+            .filter_map(|path| self.index_of_path(path))
+            .collect()
+    }
+
+    /// Returns the tracked places which are reached through the given path.
+    pub(crate) fn descendants_of(&self, path: &PlacePath) -> Vec<usize> {
+        self.iter()
+            .filter(|(_, linear_place)| path.is_prefix_of(&linear_place.path))
+            .map(|(descendant, _)| descendant)
+            .collect()
+    }
+
+    pub(crate) fn is_linear(&self, body: &mir::Body, place: &mir::Place) -> bool {
+        if place.projections.is_empty()
+            && (body.local_decls[place.local].is_linear()
+                || self.owned_temps.contains(&place.local))
+        {
+            return true;
+        }
+
+        self.index_of(place).is_some()
+    }
+
+    /// Returns the tracked place which the argument of a function call is copied from.
+    pub(crate) fn source_of(&self, place: &mir::Place) -> Option<usize> {
+        if place.projections.is_empty()
+            && let Some(index) = self.arg_sources.get(&place.local)
+        {
+            return Some(*index);
+        }
+
+        self.index_of(place)
+    }
+
+    /// Returns the name of the place and the location which it is defined at, unless it is a
+    /// temporary which has no name to be reported.
+    pub(crate) fn describe(&self, body: &mir::Body, place: &mir::Place) -> Option<(String, Span)> {
+        if let Some(index) = self.index_of(place) {
+            let linear_place = &self.places[index];
+
+            return Some((linear_place.name.clone(), linear_place.span));
+        }
+
+        let local_decl = &body.local_decls[place.local];
+
+        match &local_decl.kind {
+            mir::LocalKind::Real { ident, .. } => Some((ident.name.clone(), local_decl.span)),
+            mir::LocalKind::Temp => None,
+        }
+    }
+
+    /// The state at the beginning of the function in which only the arguments, along with
+    /// the linear fields stored in them, own a value.
+    pub(crate) fn entry_state(&self) -> LinearState {
+        LinearState(
+            self.places
+                .iter()
+                .map(|linear_place| LinearValue {
+                    status: if linear_place.is_arg && linear_place.is_in_frame() {
+                        LinearStatus::Owner
+                    } else {
+                        LinearStatus::Unknown
+                    },
+                    labels: vec![],
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Returns the place if it is a linear field which is reached through a variable.
+fn resolve_linear_field(body: &mir::Body, path: PlacePath) -> Option<LinearPlace> {
+    if path.projections.is_empty() {
+        return None;
+    }
+
+    let local_decl = &body.local_decls[path.local];
+
+    // Temporary values are only tracked for the fields which are stored in them directly.
+    let mut name = match &local_decl.kind {
+        mir::LocalKind::Real { ident, .. } => ident.name.clone(),
+        mir::LocalKind::Temp if !path.projections.contains(&PathElem::Deref) => {
+            "temporary value".to_owned()
+        }
+        mir::LocalKind::Temp => return None,
+    };
+    let mut ty_kind = local_decl.ty.kind.clone();
+    let mut is_linear = false;
+    let mut span = Span { lo: 0, hi: 0 };
+    let mut is_dereferenced = false;
+
+    for projection in &path.projections {
+        match projection {
+            PathElem::Field(field_index) => {
+                let fields = ty_kind
+                    .fields(body.type_tag_resolver, local_decl.span)
+                    .ok()?;
+                let field_ty = fields.by_index.get(*field_index)?;
+
+                let field_name = fields
+                    .by_name
+                    .iter()
+                    .find(|(_, indices)| indices.as_slice() == [*field_index])
+                    .map(|(field_name, _)| field_name.clone())
+                    .unwrap_or_else(|| field_index.to_string());
+
+                name = if is_dereferenced {
+                    format!("{name}->{field_name}")
+                } else {
+                    format!("{name}.{field_name}")
+                };
+
+                ty_kind = field_ty.kind.clone();
+                is_linear = field_ty.is_linear;
+                span = field_ty.span;
+                is_dereferenced = false;
+            }
+            PathElem::Deref => {
+                if is_dereferenced {
+                    name = format!("*{name}");
+                }
+
+                ty_kind = match ty_kind {
+                    repr::hir::TyKind::Ptr { kind, .. } | repr::hir::TyKind::Array { kind, .. } => {
+                        *kind
+                    }
+                    _ => return None,
+                };
+                is_linear = false;
+                is_dereferenced = true;
+            }
+        }
+    }
+
+    if !is_linear {
+        return None;
+    }
+
+    Some(LinearPlace {
+        name,
+        path,
+        is_arg: false,
+        span,
+    })
+}
@@ -1,7 +1,6 @@
 #![allow(clippy::missing_docs_in_private_items)]
 
 use anyhow::bail;
-use ariadne::{Fmt as _, Label, ReportBuilder};
 use log::info;
 
 use repr::{
@@ -10,331 +9,757 @@ use repr::{
 };
 
 use crate::{
-    DIAGNOSIS_REPORT_COLOR,
-    linear::{LinearCtx, LinearLocal, LinearStatus},
-    report::ReportSpan,
+    linear::{
+        LinearCtx, LinearPlaces, LinearState, LinearStatus, PathElem, PlacePath, Violation,
+        ViolationKind,
+    },
+    summary::{ParamEffect, callee_name},
 };
 
 impl LinearCtx<'_> {
     pub(crate) fn process_statement(
         &self,
         body: &mir::Body,
-        report_builder: &mut ReportBuilder<'_, ReportSpan>,
-        linear_local: &mut LinearLocal,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
         statement: &mir::Statement,
-    ) -> anyhow::Result<bool> {
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
         match &statement.kind {
             mir::StatementKind::Assign(lhs, rhs) => {
-                // TODO: Projections are ignored for the time being.
-                // if !lhs.projections.is_empty() {
-                //     return Ok(false);
-                // }
-
-                let mut is_accessed = false;
+                let mut rhs_place = None;
                 let mut rhs_is_linear = false;
+                let mut initializer = None;
 
                 match rhs {
                     mir::Rvalue::Use(operand) | mir::Rvalue::Cast { value: operand, .. } => {
                         if let mir::Operand::Place(place) = operand {
-                            // TODO: Projections are ignored for the time being.
-                            // if !place.projections.is_empty() {
-                            //     return Ok(false);
-                            // }
-
-                            if linear_local.local == place.local {
-                                is_accessed = true;
+                            if self.process_access(
+                                linear_places,
+                                state,
+                                place,
+                                statement.span,
+                                violations,
+                            ) {
+                                return Ok(());
                             }
 
-                            rhs_is_linear = body.local_decls[place.local].is_linear();
+                            rhs_place = Some(place);
+                            rhs_is_linear = linear_places.is_linear(body, place);
                         }
                     }
                     mir::Rvalue::BinaryOp(_, left_operand, right_operand)
                     | mir::Rvalue::PtrDiff(left_operand, right_operand) => {
-                        if self.process_operand(
-                            report_builder,
-                            linear_local,
-                            left_operand,
-                            statement.span,
-                        )? || self.process_operand(
-                            report_builder,
-                            linear_local,
-                            right_operand,
-                            statement.span,
-                        )? {
-                            return Ok(true);
+                        for operand in [left_operand, right_operand] {
+                            self.process_operand(
+                                linear_places,
+                                state,
+                                operand,
+                                statement.span,
+                                violations,
+                            );
                         }
 
-                        if lhs.local != linear_local.local {
-                            return Ok(false);
+                        if !self.process_access(
+                            linear_places,
+                            state,
+                            lhs,
+                            statement.span,
+                            violations,
+                        ) {
+                            self.process_non_linear_assignment(
+                                linear_places,
+                                state,
+                                lhs,
+                                statement.span,
+                                violations,
+                            );
                         }
 
-                        report_builder.set_message("Assignment of non-linear to linear");
-
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(statement.span))
-                                .with_message(format!(
-                                    "Cannot store a non-linear value in {} which is defined as linear",
-                                    format!("`{}`",linear_local.name)
-                                        .fg(DIAGNOSIS_REPORT_COLOR),
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
-
-                        report_builder.add_help("Try to store the value in a non-linear variable");
-
-                        return Ok(true);
+                        return Ok(());
                     }
                     mir::Rvalue::UnaryOp(_, operand) => {
-                        if self.process_operand(
-                            report_builder,
-                            linear_local,
+                        self.process_operand(
+                            linear_places,
+                            state,
                             operand,
                             statement.span,
-                        )? {
-                            return Ok(true);
-                        }
-
-                        if lhs.local != linear_local.local {
-                            return Ok(false);
-                        }
-
-                        report_builder.set_message("Assignment of non-linear to linear");
-
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(statement.span))
-                                .with_message(format!(
-                                    "Cannot store a non-linear value in {} which is defined as linear",
-                                    format!("`{}`",linear_local.name)
-                                        .fg(DIAGNOSIS_REPORT_COLOR),
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
+                            violations,
                         );
 
-                        report_builder.add_help("Try to store the value in a non-linear variable");
+                        if !self.process_access(
+                            linear_places,
+                            state,
+                            lhs,
+                            statement.span,
+                            violations,
+                        ) {
+                            self.process_non_linear_assignment(
+                                linear_places,
+                                state,
+                                lhs,
+                                statement.span,
+                                violations,
+                            );
+                        }
 
-                        return Ok(true);
+                        return Ok(());
                     }
                     mir::Rvalue::Call(..) => {
                         return self.process_func_call(
                             body,
-                            report_builder,
-                            linear_local,
+                            linear_places,
+                            state,
                             statement,
+                            violations,
                         );
                     }
+                    mir::Rvalue::CompoundInitializing(_, tree) => initializer = Some(tree),
                     mir::Rvalue::VaArg(..)
-                    | mir::Rvalue::CompoundInitializing(..)
                     | mir::Rvalue::AddrOf(_)
                     | mir::Rvalue::AddrOfStatic(_)
                     | mir::Rvalue::Empty => {}
                 }
 
-                if is_accessed {
-                    let lhs_decl = &body.local_decls[lhs.local];
+                if self.process_access(linear_places, state, lhs, statement.span, violations) {
+                    return Ok(());
+                }
+
+                let lhs_is_linear = linear_places.is_linear(body, lhs);
+                let lhs_is_borrowed =
+                    lhs.projections.is_empty() && body.local_decls[lhs.local].is_borrowed();
 
-                    let lhs_name = match &lhs_decl.kind {
-                        mir::LocalKind::Real { ident, .. } => ident.name.clone(),
-                        mir::LocalKind::Temp => "unknown".to_owned(),
-                    };
+                self.process_overwrite(linear_places, state, lhs, statement.span, violations);
 
-                    match (lhs_decl.is_linear(), &linear_local.status) {
+                if let Some(tree) = initializer {
+                    self.process_initializer(
+                        linear_places,
+                        state,
+                        lhs,
+                        tree,
+                        statement.span,
+                        violations,
+                    );
+                }
+
+                // Casting to `void *` hides the fields which are reached through the value, so
+                // they can no longer be moved by its new owner.
+                if let Some(index) = rhs_place.and_then(|place| linear_places.index_of(place))
+                    && lhs_is_linear
+                    && matches!(
+                        rhs,
+                        mir::Rvalue::Cast {
+                            to_type: hir::TyKind::Ptr { kind, .. },
+                            ..
+                        } if kind.is_void()
+                    )
+                {
+                    self.process_drop(linear_places, state, index, statement.span, violations);
+                }
+
+                // A borrowed value is only lent to its user, so the fields stored in it are
+                // still owned by the borrowed place.
+                if let Some(rhs_place) = rhs_place
+                    && !lhs_is_borrowed
+                {
+                    self.process_transfer(
+                        linear_places,
+                        state,
+                        lhs,
+                        rhs_place,
+                        lhs_is_linear,
+                        statement.span,
+                    );
+                }
+
+                if let Some(index) = rhs_place.and_then(|place| linear_places.index_of(place)) {
+                    let linear_place = &linear_places[index];
+
+                    let lhs_decl = linear_places.describe(body, lhs);
+
+                    match (lhs_is_linear, state[index].status) {
                         (true, LinearStatus::Owner | LinearStatus::Unknown) => {
-                            linear_local.set_free();
-
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(statement.span))
-                                    .with_message(format!(
-                                        "{}'s value is moved in here",
-                                        format!("`{}`", linear_local.name)
-                                            .fg(DIAGNOSIS_REPORT_COLOR),
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
-                            );
+                            self.move_place(linear_places, state, index, statement.span);
                         }
-                        (true, LinearStatus::Free) => {
-                            report_builder.set_message("Use of moved value");
-
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(lhs_decl.span))
-                                    .with_message(format!(
-                                        "Variable {} is defined in here as linear",
-                                        format!("`{lhs_name}`").fg(DIAGNOSIS_REPORT_COLOR)
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
+                        (true, LinearStatus::Free | LinearStatus::MaybeOwned) => {
+                            violations.push(
+                                Violation::of_place(
+                                    ViolationKind::UseOfMovedValue,
+                                    statement.span,
+                                    linear_places,
+                                    index,
+                                    &state[index],
+                                )
+                                .with_target_labels(
+                                    lhs_decl.as_ref(),
+                                    "linear",
+                                    format!(
+                                        "Cannot move {}'s invalid value",
+                                        linear_place.quoted_name()
+                                    ),
+                                    statement.span,
+                                )
+                                .with_help(format!(
+                                    "Try to move a value to {} before reaching this statement",
+                                    linear_place.quoted_name()
+                                )),
                             );
 
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(statement.span))
-                                    .with_message(format!(
-                                        "Cannot move {}'s invalid value to {}",
-                                        format!("`{}`", linear_local.name)
-                                            .fg(DIAGNOSIS_REPORT_COLOR),
-                                        format!("`{lhs_name}`").fg(DIAGNOSIS_REPORT_COLOR)
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
-                            );
-
-                            report_builder.add_help(format!(
-                                "Try to move a value to {} before reaching this statement",
-                                format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                            ));
+                            state[index].reset();
 
-                            return Ok(true);
+                            return Ok(());
                         }
                         (false, LinearStatus::Owner | LinearStatus::Unknown) => (),
-                        (false, LinearStatus::Free) => {
-                            report_builder.set_message("Use of moved value");
-
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(lhs_decl.span))
-                                    .with_message(format!(
-                                        "Variable {} is defined in here as non-linear",
-                                        format!("`{lhs_name}`").fg(DIAGNOSIS_REPORT_COLOR)
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
+                        (false, LinearStatus::Free | LinearStatus::MaybeOwned)
+                            if lhs_is_borrowed =>
+                        {
+                            self.process_invalid_borrow(
+                                linear_places,
+                                state,
+                                index,
+                                statement.span,
+                                violations,
                             );
 
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(statement.span))
-                                    .with_message(format!(
-                                        "Cannot lend {}'s invalid value to {}",
-                                        format!("`{}`", linear_local.name)
-                                            .fg(DIAGNOSIS_REPORT_COLOR),
-                                        format!("`{lhs_name}`").fg(DIAGNOSIS_REPORT_COLOR)
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
+                            return Ok(());
+                        }
+                        (false, LinearStatus::Free | LinearStatus::MaybeOwned) => {
+                            violations.push(
+                                Violation::of_place(
+                                    ViolationKind::UseOfMovedValue,
+                                    statement.span,
+                                    linear_places,
+                                    index,
+                                    &state[index],
+                                )
+                                .with_target_labels(
+                                    lhs_decl.as_ref(),
+                                    "non-linear",
+                                    format!(
+                                        "Cannot lend {}'s invalid value",
+                                        linear_place.quoted_name()
+                                    ),
+                                    statement.span,
+                                )
+                                .with_help(format!(
+                                    "Try to move a value to {} before reaching this statement",
+                                    linear_place.quoted_name()
+                                )),
                             );
 
-                            report_builder.add_help(format!(
-                                "Try to move a value to {} before reaching this statement",
-                                format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                            ));
+                            state[index].reset();
 
-                            return Ok(true);
+                            return Ok(());
                         }
                     }
                 }
 
                 // TODO: Better reports when a non-linear is assigned to a linear.
-                if lhs.local == linear_local.local {
-                    if !rhs_is_linear {
-                        report_builder.set_message("Assignment of non-linear to linear");
-
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(statement.span))
-                                .with_message(format!(
-                                    "Cannot store a non-linear value in {} which is defined as linear",
-                                    format!("`{}`",linear_local.name)
-                                        .fg(DIAGNOSIS_REPORT_COLOR),
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
+                if !rhs_is_linear {
+                    self.process_non_linear_assignment(
+                        linear_places,
+                        state,
+                        lhs,
+                        statement.span,
+                        violations,
+                    );
+                } else if let Some(index) = linear_places.index_of(lhs) {
+                    self.process_new_value(linear_places, state, index, statement.span, violations);
+                }
 
-                        report_builder.add_help("Try to store the value in a non-linear variable");
+                Ok(())
+            }
+            mir::StatementKind::Call(..) => {
+                self.process_func_call(body, linear_places, state, statement, violations)
+            }
+        }
+    }
 
-                        return Ok(true);
-                    }
+    /// Reports accessing the place through a tracked place which does not own a value and
+    /// returns whether it has happened.
+    fn process_access(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        place: &mir::Place,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) -> bool {
+        for index in linear_places.prefixes_of(place) {
+            if !matches!(
+                state[index].status,
+                LinearStatus::Free | LinearStatus::MaybeOwned
+            ) {
+                continue;
+            }
 
-                    match linear_local.status {
-                        LinearStatus::Owner => {
-                            report_builder.set_message("Overwriting owned value");
-
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(statement.span))
-                                    .with_message(format!(
-                                        "Current owned value of {} is overwritten in here",
-                                        format!("`{}`", linear_local.name)
-                                            .fg(DIAGNOSIS_REPORT_COLOR)
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
-                            );
+            let linear_place = &linear_places[index];
 
-                            report_builder.add_help(format!(
-                                "Try to move {}'s value before reaching this statement",
-                                format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                            ));
+            violations.push(
+                Violation::of_place(
+                    ViolationKind::UseOfMovedValue,
+                    place.span,
+                    linear_places,
+                    index,
+                    &state[index],
+                )
+                .with_label(
+                    format!(
+                        "Cannot access through {}'s invalid value",
+                        linear_place.quoted_name()
+                    ),
+                    stmt_span,
+                )
+                .with_help(format!(
+                    "Try to move a value to {} before reaching this statement",
+                    linear_place.quoted_name()
+                )),
+            );
 
-                            return Ok(true);
-                        }
-                        LinearStatus::Free | LinearStatus::Unknown => {
-                            linear_local.set_owner();
-
-                            report_builder.add_label(
-                                Label::new(ReportSpan::new(statement.span))
-                                    .with_message(format!(
-                                        "A new value is moved to {} in here",
-                                        format!("`{}`", linear_local.name)
-                                            .fg(DIAGNOSIS_REPORT_COLOR),
-                                    ))
-                                    .with_color(DIAGNOSIS_REPORT_COLOR),
-                            );
-                        }
-                    }
-                }
+            state[index].reset();
 
-                Ok(false)
-            }
-            mir::StatementKind::Call(..) => {
-                self.process_func_call(body, report_builder, linear_local, statement)
-            }
+            return true;
         }
+
+        false
     }
 
     fn process_operand(
         &self,
-        report_builder: &mut ReportBuilder<'_, ReportSpan>,
-        linear_local: &mut LinearLocal,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
         operand: &mir::Operand,
         stmt_span: Span,
-    ) -> anyhow::Result<bool> {
+        violations: &mut Vec<Violation>,
+    ) {
         let mir::Operand::Place(place) = operand else {
-            return Ok(false);
+            return;
         };
 
-        // TODO: Projections are ignored for the time being.
-        if !place.projections.is_empty() {
-            return Ok(false);
+        if self.process_access(linear_places, state, place, stmt_span, violations) {
+            return;
         }
 
-        if linear_local.local != place.local {
-            return Ok(false);
-        }
+        let Some(index) = linear_places.index_of(place) else {
+            return;
+        };
 
-        match linear_local.status {
-            LinearStatus::Owner => return Ok(false),
+        let linear_place = &linear_places[index];
+        let linear_value = &mut state[index];
+
+        match linear_value.status {
+            LinearStatus::Owner => return,
             LinearStatus::Unknown => {
-                linear_local.set_owner();
-                return Ok(false);
+                linear_value.set_owner();
+                return;
             }
-            LinearStatus::Free => (),
+            LinearStatus::Free | LinearStatus::MaybeOwned => (),
         }
 
-        report_builder.set_message("Use of moved value");
+        violations.push(
+            Violation::of_place(
+                ViolationKind::UseOfMovedValue,
+                stmt_span,
+                linear_places,
+                index,
+                linear_value,
+            )
+            .with_label(
+                format!("Cannot lend {}'s invalid value", linear_place.quoted_name()),
+                stmt_span,
+            )
+            .with_help(format!(
+                "Try to move a value to {} before reaching this statement",
+                linear_place.quoted_name()
+            )),
+        );
 
-        report_builder.add_label(
-            Label::new(ReportSpan::new(stmt_span))
-                .with_message(format!(
-                    "Cannot lend {}'s invalid value.",
-                    format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR),
-                ))
-                .with_color(DIAGNOSIS_REPORT_COLOR),
+        linear_value.reset();
+    }
+
+    /// Moves the value out of the tracked place along with every place reached through it.
+    fn move_place(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        index: usize,
+        span: Span,
+    ) {
+        let linear_place = &linear_places[index];
+
+        state[index].set_free();
+        state[index].add_label(
+            format!("{}'s value is moved in here", linear_place.quoted_name()),
+            span,
         );
 
-        report_builder.add_help(format!(
-            "Try to move a value to {} before reaching this statement",
-            format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-        ));
+        for descendant in linear_places.descendants_of(&linear_place.path) {
+            state[descendant].set_free();
+        }
+    }
+
+    /// Forgets the places reached through the overwritten place and reports the owned fields
+    /// which are stored in it directly.
+    fn process_overwrite(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        lhs: &mir::Place,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let Some(path) = PlacePath::from_place(lhs) else {
+            return;
+        };
+
+        for descendant in linear_places.descendants_of(&path) {
+            let linear_place = &linear_places[descendant];
 
-        Ok(true)
+            if linear_place.path.is_stored_in(&path)
+                && matches!(
+                    state[descendant].status,
+                    LinearStatus::Owner | LinearStatus::MaybeOwned
+                )
+            {
+                violations.push(
+                    Violation::of_place(
+                        ViolationKind::OverwritingOwnedValue,
+                        stmt_span,
+                        linear_places,
+                        descendant,
+                        &state[descendant],
+                    )
+                    .with_label(
+                        format!(
+                            "Current owned value of {} is overwritten in here",
+                            linear_place.quoted_name()
+                        ),
+                        stmt_span,
+                    )
+                    .with_help(format!(
+                        "Try to move {}'s value before reaching this statement",
+                        linear_place.quoted_name()
+                    )),
+                );
+            }
+
+            state[descendant].reset();
+        }
+    }
+
+    /// Moves the values of the places reached through the right-hand side to the matching
+    /// places of the left-hand side when the right-hand side is either moved or copied as a
+    /// compound value.
+    fn process_transfer(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        lhs: &mir::Place,
+        rhs: &mir::Place,
+        lhs_is_linear: bool,
+        stmt_span: Span,
+    ) {
+        let (Some(lhs_path), Some(rhs_path)) =
+            (PlacePath::from_place(lhs), PlacePath::from_place(rhs))
+        else {
+            return;
+        };
+
+        for descendant in linear_places.descendants_of(&rhs_path) {
+            let suffix = &linear_places[descendant].path.projections[rhs_path.projections.len()..];
+
+            if !lhs_is_linear && !matches!(suffix.first(), Some(PathElem::Field(_))) {
+                continue;
+            }
+
+            let mut target_path = lhs_path.clone();
+            target_path.projections.extend_from_slice(suffix);
+
+            if let Some(target) = linear_places.index_of_path(&target_path) {
+                let status = state[descendant].status;
+                let target_place = &linear_places[target];
+
+                state[target].status = status;
+                state[target].add_label(
+                    format!(
+                        "A new value is moved to {} in here",
+                        target_place.quoted_name()
+                    ),
+                    stmt_span,
+                );
+            }
+
+            if !matches!(state[descendant].status, LinearStatus::Unknown) {
+                let descendant_place = &linear_places[descendant];
+
+                state[descendant].set_free();
+                state[descendant].add_label(
+                    format!(
+                        "{}'s value is moved in here",
+                        descendant_place.quoted_name()
+                    ),
+                    stmt_span,
+                );
+            }
+        }
+    }
+
+    /// Moves the values of an initializer list to the linear fields which they initialize.
+    fn process_initializer(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        lhs: &mir::Place,
+        tree: &mir::MirInitializerTree,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let Some(path) = PlacePath::from_place(lhs) else {
+            return;
+        };
+
+        'descendants: for descendant in linear_places.descendants_of(&path) {
+            let mut node = tree;
+
+            for projection in &linear_places[descendant].path.projections[path.projections.len()..]
+            {
+                let (PathElem::Field(field_index), mir::MirInitializerTree::Middle { children }) =
+                    (projection, node)
+                else {
+                    continue 'descendants;
+                };
+
+                let Some(child) = children.get(*field_index) else {
+                    continue 'descendants;
+                };
+
+                node = child;
+            }
+
+            let mir::MirInitializerTree::Leaf(mir::Operand::Place(place)) = node else {
+                continue;
+            };
+
+            if let Some(index) = linear_places.index_of(place) {
+                match state[index].status {
+                    LinearStatus::Owner | LinearStatus::Unknown => {
+                        self.move_place(linear_places, state, index, stmt_span);
+                    }
+                    LinearStatus::Free | LinearStatus::MaybeOwned => {
+                        let linear_place = &linear_places[index];
+
+                        violations.push(
+                            Violation::of_place(
+                                ViolationKind::UseOfMovedValue,
+                                stmt_span,
+                                linear_places,
+                                index,
+                                &state[index],
+                            )
+                            .with_label(
+                                format!(
+                                    "Cannot move {}'s invalid value to {}",
+                                    linear_place.quoted_name(),
+                                    linear_places[descendant].quoted_name()
+                                ),
+                                stmt_span,
+                            )
+                            .with_help(format!(
+                                "Try to move a value to {} before reaching this statement",
+                                linear_place.quoted_name()
+                            )),
+                        );
+
+                        state[index].reset();
+
+                        continue;
+                    }
+                }
+            }
+
+            self.process_new_value(linear_places, state, descendant, stmt_span, violations);
+        }
+    }
+
+    /// Reports the linear places reached through the tracked place which still own a value
+    /// when it is dropped.
+    fn process_drop(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        index: usize,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let linear_place = &linear_places[index];
+
+        for descendant in linear_places.descendants_of(&linear_place.path) {
+            if !matches!(
+                state[descendant].status,
+                LinearStatus::Owner | LinearStatus::MaybeOwned
+            ) {
+                continue;
+            }
+
+            let descendant_place = &linear_places[descendant];
+
+            violations.push(
+                Violation::of_place(
+                    ViolationKind::DroppedOwnedField,
+                    stmt_span,
+                    linear_places,
+                    descendant,
+                    &state[descendant],
+                )
+                .with_label(
+                    format!(
+                        "{} is dropped in here while {} still owns a value",
+                        linear_place.quoted_name(),
+                        descendant_place.quoted_name()
+                    ),
+                    stmt_span,
+                )
+                .with_help(format!(
+                    "Try to move {}'s value before dropping {}",
+                    descendant_place.quoted_name(),
+                    linear_place.quoted_name()
+                )),
+            );
+
+            state[descendant].reset();
+        }
+    }
+
+    /// Reports storing a non-linear value in the left-hand side if it is a linear place.
+    fn process_non_linear_assignment(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        lhs: &mir::Place,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let Some(index) = linear_places.index_of(lhs) else {
+            return;
+        };
+
+        let linear_place = &linear_places[index];
+
+        violations.push(
+            Violation::of_place(
+                ViolationKind::NonLinearAssignment,
+                stmt_span,
+                linear_places,
+                index,
+                &state[index],
+            )
+            .with_label(
+                format!(
+                    "Cannot store a non-linear value in {} which is defined as linear",
+                    linear_place.quoted_name()
+                ),
+                stmt_span,
+            )
+            .with_help("Try to store the value in a non-linear variable"),
+        );
+
+        state[index].reset();
+    }
+
+    /// Moves a new linear value to the linear place unless it still owns a value.
+    fn process_new_value(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        index: usize,
+        stmt_span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let linear_place = &linear_places[index];
+        let linear_value = &mut state[index];
+
+        match linear_value.status {
+            LinearStatus::Owner | LinearStatus::MaybeOwned => {
+                violations.push(
+                    Violation::of_place(
+                        ViolationKind::OverwritingOwnedValue,
+                        stmt_span,
+                        linear_places,
+                        index,
+                        linear_value,
+                    )
+                    .with_label(
+                        format!(
+                            "Current owned value of {} is overwritten in here",
+                            linear_place.quoted_name()
+                        ),
+                        stmt_span,
+                    )
+                    .with_help(format!(
+                        "Try to move {}'s value before reaching this statement",
+                        linear_place.quoted_name()
+                    )),
+                );
+
+                linear_value.reset();
+            }
+            LinearStatus::Free | LinearStatus::Unknown => {
+                linear_value.set_owner();
+
+                linear_value.add_label(
+                    format!(
+                        "A new value is moved to {} in here",
+                        linear_place.quoted_name()
+                    ),
+                    stmt_span,
+                );
+            }
+        }
+    }
+
+    /// Reports borrowing a tracked place which does not own a value.
+    fn process_invalid_borrow(
+        &self,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
+        index: usize,
+        span: Span,
+        violations: &mut Vec<Violation>,
+    ) {
+        let linear_place = &linear_places[index];
+
+        violations.push(
+            Violation::of_place(
+                ViolationKind::UseOfMovedValue,
+                span,
+                linear_places,
+                index,
+                &state[index],
+            )
+            .with_label(
+                format!(
+                    "Cannot borrow {}'s invalid value",
+                    linear_place.quoted_name(),
+                ),
+                span,
+            )
+            .with_help(format!(
+                "Try to move a value to {} before reaching this statement",
+                linear_place.quoted_name()
+            )),
+        );
+
+        state[index].reset();
     }
 
     fn process_func_call(
         &self,
         body: &mir::Body,
-        report_builder: &mut ReportBuilder<'_, ReportSpan>,
-        linear_local: &mut LinearLocal,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
         statement: &mir::Statement,
-    ) -> anyhow::Result<bool> {
+        violations: &mut Vec<Violation>,
+    ) -> anyhow::Result<()> {
         let (lhs, func, params) = match &statement.kind {
             mir::StatementKind::Assign(lhs, rvalue) => match rvalue {
                 mir::Rvalue::Call(func, params) => (Some(lhs), func, params),
@@ -382,6 +807,8 @@ impl LinearCtx<'_> {
             },
         };
 
+        let summary = callee_name(body, func).and_then(|func_name| self.summaries.get(func_name));
+
         if func_sig.variadic_param {
             if params.len() < func_sig.params.len() {
                 bail!("Invalid number of arguments for the function call.");
@@ -398,51 +825,55 @@ impl LinearCtx<'_> {
             }
         }
 
-        for (param_operand, func_param_decl) in params.iter().zip(func_sig.params.iter()) {
+        for param_operand in params {
             let mir::Operand::Place(param_place) = param_operand else {
                 continue;
             };
 
-            if !func_param_decl.ty.is_linear {
+            if self.process_access(
+                linear_places,
+                state,
+                param_place,
+                param_place.span,
+                violations,
+            ) {
                 continue;
             }
 
-            if linear_local.local != param_place.local {
-                // TODO: Projections are ignored for the time being.
-                if !body.local_decls[param_place.local].is_linear()
-                    && param_place.projections.is_empty()
-                {
-                    info!("Not supported yet - Passed non-linear as linear in function call.");
-
-                    report_builder.add_label(
-                        Label::new(ReportSpan::new(param_place.span))
-                            .with_message("Passed non-linear as linear in function call")
-                            .with_color(DIAGNOSIS_REPORT_COLOR),
-                    );
-
-                    return Ok(true);
-                }
-
+            // A compound value passed to a function moves the linear fields stored in it.
+            let Some(path) = PlacePath::from_place(param_place) else {
                 continue;
-            }
+            };
 
-            match linear_local.status {
-                LinearStatus::Owner | LinearStatus::Unknown => {
-                    linear_local.set_free();
-
-                    report_builder.add_label(
-                        Label::new(ReportSpan::new(param_place.span))
-                            .with_message(format!(
-                                "{}'s value is moved in here",
-                                format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR),
-                            ))
-                            .with_color(DIAGNOSIS_REPORT_COLOR),
-                    );
+            for descendant in linear_places.descendants_of(&path) {
+                let descendant_place = &linear_places[descendant];
 
+                if !descendant_place.path.is_stored_in(&path)
+                    || !matches!(
+                        state[descendant].status,
+                        LinearStatus::Owner | LinearStatus::MaybeOwned
+                    )
+                {
                     continue;
                 }
-                LinearStatus::Free => (),
+
+                state[descendant].set_free();
+                state[descendant].add_label(
+                    format!(
+                        "{}'s value is moved in here",
+                        descendant_place.quoted_name()
+                    ),
+                    param_place.span,
+                );
             }
+        }
+
+        for (position, (param_operand, func_param_decl)) in
+            params.iter().zip(func_sig.params.iter()).enumerate()
+        {
+            let mir::Operand::Place(param_place) = param_operand else {
+                continue;
+            };
 
             let func_param_name = func_param_decl
                 .ident
@@ -450,142 +881,215 @@ impl LinearCtx<'_> {
                 .map(|ident| ident.name)
                 .unwrap_or_default();
 
-            report_builder.set_message("Use of moved value");
+            // A borrowed parameter only lends the value to the function. The arguments which
+            // are copied to a borrowed temporary are already checked by their assignment.
+            if func_param_decl.ty.is_borrowed {
+                if !body.local_decls[param_place.local].is_borrowed()
+                    && let Some(index) = linear_places.source_of(param_place)
+                    && matches!(
+                        state[index].status,
+                        LinearStatus::Free | LinearStatus::MaybeOwned
+                    )
+                {
+                    self.process_invalid_borrow(
+                        linear_places,
+                        state,
+                        index,
+                        param_place.span,
+                        violations,
+                    );
+                }
 
-            report_builder.add_label(
-                Label::new(ReportSpan::new(decl_span))
-                    .with_message(format!(
-                        "Function {} is defined in here which captures parameter {} as linear",
-                        format!("`{func_name}`").fg(DIAGNOSIS_REPORT_COLOR),
-                        format!("`{func_param_name}`",).fg(DIAGNOSIS_REPORT_COLOR)
-                    ))
-                    .with_color(DIAGNOSIS_REPORT_COLOR),
-            );
+                continue;
+            }
+
+            // Unannotated parameters which are inferred to take the ownership of the passed
+            // value are treated as linear ones.
+            let effect = summary
+                .and_then(|summary| summary.effect_of(position))
+                .filter(|effect| matches!(effect, ParamEffect::Consumes | ParamEffect::Stores));
+
+            let index = if func_param_decl.ty.is_linear {
+                let Some(index) = linear_places.index_of(param_place) else {
+                    // TODO: Places with index projections are ignored for the time being.
+                    if !body.local_decls[param_place.local].is_linear()
+                        && param_place.projections.is_empty()
+                    {
+                        info!("Not supported yet - Passed non-linear as linear in function call.");
+
+                        violations.push(
+                            Violation::new(ViolationKind::NonLinearArgument, param_place.span)
+                                .with_label(
+                                    "Passed non-linear as linear in function call",
+                                    param_place.span,
+                                ),
+                        );
+                    }
+
+                    continue;
+                };
+
+                index
+            } else if let Some(effect) = effect
+                && let Some(index) = linear_places.source_of(param_place)
+            {
+                if matches!(
+                    state[index].status,
+                    LinearStatus::Owner | LinearStatus::Unknown
+                ) {
+                    state[index].add_label(
+                        format!("Function `{func_name}` is defined in here which {} parameter `{func_param_name}`", effect.describe()),
+                        decl_span,
+                    );
+                }
+
+                index
+            } else {
+                continue;
+            };
+
+            let linear_place = &linear_places[index];
 
-            report_builder.add_label(
-                Label::new(ReportSpan::new(param_place.span))
-                    .with_message(format!(
+            match state[index].status {
+                LinearStatus::Owner | LinearStatus::Unknown => {
+                    self.move_place(linear_places, state, index, param_place.span);
+
+                    continue;
+                }
+                LinearStatus::Free | LinearStatus::MaybeOwned => (),
+            }
+
+            violations.push(
+                Violation::of_place(
+                    ViolationKind::UseOfMovedValue,
+                    param_place.span,
+                    linear_places,
+                    index,
+                    &state[index],
+                )
+                .with_label(
+                    match effect {
+                        Some(effect) if !func_param_decl.ty.is_linear => format!("Function `{func_name}` is defined in here which {} parameter `{func_param_name}`", effect.describe()),
+                        _ => format!("Function `{func_name}` is defined in here which captures parameter `{func_param_name}` as linear"),
+                    },
+                    decl_span,
+                )
+                .with_label(
+                    format!(
                         "Cannot move and pass {}'s invalid value",
-                        format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR),
-                    ))
-                    .with_color(DIAGNOSIS_REPORT_COLOR),
+                        linear_place.quoted_name()
+                    ),
+                    param_place.span,
+                )
+                .with_help(format!(
+                    "Try to move a value to {} before reaching this statement",
+                    linear_place.quoted_name()
+                )),
             );
 
-            report_builder.add_help(format!(
-                "Try to move a value to {} before reaching this statement",
-                format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-            ));
-
-            return Ok(true);
+            state[index].reset();
         }
 
+        // A function which is inferred to return an owned value is treated as a linear one.
+        let returns_linear =
+            func_sig.ret_ty.is_linear || summary.is_some_and(|summary| summary.returns_owned);
+
         let Some(lhs) = lhs else {
-            if func_sig.ret_ty.is_linear {
+            if returns_linear {
                 info!("Not supported yet - Ignored linear result after function call.");
 
-                report_builder.add_label(
-                    Label::new(ReportSpan::new(statement.span))
-                        .with_message("Ignored linear result after function call")
-                        .with_color(DIAGNOSIS_REPORT_COLOR),
+                violations.push(
+                    Violation::new(ViolationKind::IgnoredLinearResult, statement.span)
+                        .with_label("Ignored linear result after function call", statement.span),
                 );
-
-                return Ok(true);
             }
 
-            return Ok(false);
+            return Ok(());
         };
 
-        let lhs_decl = &body.local_decls[lhs.local];
-
-        if lhs.local == linear_local.local {
-            if func_sig.ret_ty.is_linear {
-                match linear_local.status {
-                    LinearStatus::Owner => {
-                        report_builder.set_message("Overwriting owned value");
-
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(statement.span))
-                                .with_message(format!(
-                                    "Current owned value of {} is overwritten in here",
-                                    format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
+        if self.process_access(linear_places, state, lhs, statement.span, violations) {
+            return Ok(());
+        }
 
-                        report_builder.add_help(format!(
-                            "Try to move {}'s value before reaching this statement",
-                            format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                        ));
+        self.process_overwrite(linear_places, state, lhs, statement.span, violations);
 
-                        return Ok(true);
-                    }
-                    LinearStatus::Free | LinearStatus::Unknown => {
-                        linear_local.set_owner();
-
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(statement.span))
-                                .with_message(format!(
-                                    "A new value is moved to {} in here",
-                                    format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR),
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
-                    }
+        // A compound value returned from a function owns the linear fields stored in it.
+        if let Some(path) = PlacePath::from_place(lhs) {
+            for descendant in linear_places.descendants_of(&path) {
+                if !linear_places[descendant].path.is_stored_in(&path) {
+                    continue;
                 }
-            } else {
-                report_builder.set_message("Assignment of non-linear to linear");
-
-                report_builder.add_label(
-                    Label::new(ReportSpan::new(decl_span))
-                        .with_message(format!(
-                            "Function {} is defined in here which does not return a linear value",
-                            format!("`{func_name}`").fg(DIAGNOSIS_REPORT_COLOR),
-                        ))
-                        .with_color(DIAGNOSIS_REPORT_COLOR),
+
+                self.process_new_value(
+                    linear_places,
+                    state,
+                    descendant,
+                    statement.span,
+                    violations,
                 );
+            }
+        }
 
-                report_builder.add_label(
-                    Label::new(ReportSpan::new(statement.span))
-                        .with_message(format!(
+        if let Some(index) = linear_places.index_of(lhs) {
+            if returns_linear {
+                self.process_new_value(linear_places, state, index, statement.span, violations);
+            } else {
+                let linear_place = &linear_places[index];
+
+                violations.push(
+                    Violation::of_place(
+                        ViolationKind::NonLinearAssignment,
+                        statement.span,
+                        linear_places,
+                        index,
+                        &state[index],
+                    )
+                    .with_label(
+                        format!("Function `{func_name}` is defined in here which does not return a linear value"),
+                        decl_span,
+                    )
+                    .with_label(
+                        format!(
                             "Cannot store a non-linear value in {} which is defined as linear",
-                            format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR),
-                        ))
-                        .with_color(DIAGNOSIS_REPORT_COLOR),
+                            linear_place.quoted_name()
+                        ),
+                        statement.span,
+                    )
+                    .with_help("Try to store the returned value in a non-linear variable"),
                 );
 
-                report_builder.add_help("Try to store the returned value in a non-linear variable");
-
-                return Ok(true);
+                state[index].reset();
             }
 
-            return Ok(false);
+            return Ok(());
         }
 
-        // TODO: Projections are ignored for the time being.
+        // TODO: Places with index projections are ignored for the time being.
         if lhs.projections.is_empty() {
-            if lhs_decl.is_linear() && !func_sig.ret_ty.is_linear {
+            let lhs_is_linear = linear_places.is_linear(body, lhs);
+
+            if lhs_is_linear && !returns_linear {
                 info!("Not supported yet - Stored non-linear as linear after function call.");
 
-                report_builder.add_label(
-                    Label::new(ReportSpan::new(statement.span))
-                        .with_message("Stored non-linear as linear after function call")
-                        .with_color(DIAGNOSIS_REPORT_COLOR),
+                violations.push(
+                    Violation::new(ViolationKind::NonLinearResultStored, statement.span)
+                        .with_label(
+                            "Stored non-linear as linear after function call",
+                            statement.span,
+                        ),
                 );
-
-                return Ok(true);
-            } else if !lhs_decl.is_linear() && func_sig.ret_ty.is_linear {
+            } else if !lhs_is_linear && returns_linear {
                 info!("Not supported yet - Stored linear as non-linear after function call.");
 
-                report_builder.add_label(
-                    Label::new(ReportSpan::new(statement.span))
-                        .with_message("Stored linear as non-linear after function call.")
-                        .with_color(DIAGNOSIS_REPORT_COLOR),
+                violations.push(
+                    Violation::new(ViolationKind::LinearResultStored, statement.span).with_label(
+                        "Stored linear as non-linear after function call",
+                        statement.span,
+                    ),
                 );
-
-                return Ok(true);
             }
         }
 
-        Ok(false)
+        Ok(())
     }
 }
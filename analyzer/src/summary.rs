@@ -0,0 +1,413 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use repr::{
+    hir::{self, resolver},
+    mir::{self, RETURN_LOCAL},
+};
+
+use crate::linear::{LinearCtx, LinearPlaces, LinearState, LinearStatus, PlacePath};
+
+/// The effect of a function on the value passed to one of its unannotated parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ParamEffect {
+    /// The value is moved on every path of the function, e.g. by being freed.
+    Consumes,
+    /// The value is only used and still owned by the caller.
+    Borrows,
+    /// The value is stored in a place which outlives the function.
+    Stores,
+}
+
+impl ParamEffect {
+    pub(crate) fn describe(self) -> &'static str {
+        match self {
+            ParamEffect::Consumes => "consumes",
+            ParamEffect::Borrows => "borrows",
+            ParamEffect::Stores => "stores",
+        }
+    }
+}
+
+/// The ownership behavior of a function which is inferred from its body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuncSummary {
+    /// The effects on the parameters by their position, since the declarations seen by the
+    /// callers may name them differently.
+    pub(crate) params: Vec<Option<ParamEffect>>,
+    pub(crate) returns_owned: bool,
+}
+
+impl FuncSummary {
+    pub(crate) fn effect_of(&self, position: usize) -> Option<ParamEffect> {
+        self.params.get(position).copied().flatten()
+    }
+}
+
+impl LinearCtx<'_> {
+    /// Infers the summaries of the functions defined in the translation unit, which are then
+    /// used for the calls to the functions in place of their declared types.
+    pub fn infer_summaries(&mut self, bodies: &[mir::Body]) {
+        self.infer_summaries_with(bodies, &HashMap::new());
+    }
+
+    /// Infers the summaries of the functions like [`Self::infer_summaries`], while the
+    /// summaries of the other functions are already known, such as the functions which are
+    /// not changed since their summaries were inferred.
+    pub fn infer_summaries_with(
+        &mut self,
+        bodies: &[mir::Body],
+        known_summaries: &HashMap<String, FuncSummary>,
+    ) {
+        self.summaries.clone_from(known_summaries);
+
+        // The summaries of the callers depend on the summaries of their callees, so they are
+        // inferred again until nothing changes.
+        for _ in 0..=bodies.len() {
+            let mut inferred_summaries = HashMap::new();
+            let mut ambiguous_names = HashSet::new();
+
+            for (name, summary) in bodies.iter().filter_map(|body| self.summarize(body)) {
+                if inferred_summaries.insert(name.clone(), summary).is_some() {
+                    ambiguous_names.insert(name);
+                }
+            }
+
+            let mut summaries = known_summaries.clone();
+            summaries.extend(inferred_summaries);
+
+            // The static functions of different translation units may share a name, in which
+            // case the calls to it cannot be resolved to one of them.
+            for name in &ambiguous_names {
+                summaries.remove(name);
+            }
+
+            if summaries == self.summaries {
+                break;
+            }
+
+            self.summaries = summaries;
+        }
+    }
+
+    /// The summaries of the functions which are inferred or imported by the context.
+    pub fn summaries(&self) -> &HashMap<String, FuncSummary> {
+        &self.summaries
+    }
+
+    /// Uses the summaries inferred by the other context, such as the summaries of the
+    /// functions of the whole program.
+    pub fn import_summaries(&mut self, other: &Self) {
+        self.summaries.clone_from(&other.summaries);
+    }
+
+    fn summarize(&self, body: &mir::Body) -> Option<(String, FuncSummary)> {
+        let mir::LocalKind::Real { ident, .. } = &body.local_decls[RETURN_LOCAL].kind else {
+            return None;
+        };
+
+        let linear_places = LinearPlaces::for_summary(body, &self.summaries);
+        // The function which cannot be analyzed has no summary, while its failure is reported
+        // by its analysis.
+        let entry_states = self.solve(body, &linear_places).ok()?;
+
+        // The state of the places when the function returns, joined over all of its paths.
+        let mut exit_state: Option<LinearState> = None;
+
+        for (bb, bb_data) in body.basic_blocks.iter() {
+            let bb: mir::BasicBlock = bb.into();
+
+            if !matches!(
+                bb_data
+                    .terminator
+                    .as_ref()
+                    .map(|terminator| &terminator.kind),
+                Some(mir::TerminatorKind::Return) | None
+            ) {
+                continue;
+            }
+
+            let Some(mut state) = entry_states[bb.get_id()].clone() else {
+                continue;
+            };
+
+            if !bb_data.statements.iter().all(|statement| {
+                self.process_statement(body, &linear_places, &mut state, statement, &mut vec![])
+                    .is_ok()
+            }) {
+                continue;
+            }
+
+            match &mut exit_state {
+                Some(exit_state) => {
+                    exit_state.join(&state);
+                }
+                None => exit_state = Some(state),
+            }
+        }
+
+        let exit_state = exit_state?;
+        let stored_locals = stored_locals(body, &linear_places);
+        let sunk_locals = self.sunk_locals(body, &stored_locals);
+
+        let effect_of = |local: mir::Local| {
+            // The linearity which the definition is annotated with is what the declarations
+            // of the function in other translation units may be missing.
+            if body.local_decls[local].is_linear() {
+                return Some(ParamEffect::Consumes);
+            }
+
+            let index = linear_places.index_of_path(&PlacePath::root(local))?;
+
+            if stored_locals.contains(&local) {
+                return Some(ParamEffect::Stores);
+            }
+
+            // A value which is only moved to other variables or returned, such as a pointer
+            // which walks over the passed buffer, is not taken by the function.
+            match exit_state[index].status {
+                LinearStatus::Free if sunk_locals.contains(&local) => Some(ParamEffect::Consumes),
+                LinearStatus::Owner => Some(ParamEffect::Borrows),
+                LinearStatus::Free | LinearStatus::MaybeOwned | LinearStatus::Unknown => None,
+            }
+        };
+
+        let params = body
+            .local_decls
+            .iter()
+            .filter(|(_, local_decl)| {
+                matches!(local_decl.kind, mir::LocalKind::Real { is_arg: true, .. })
+            })
+            .map(|(local, _)| effect_of(local))
+            .collect();
+
+        // A returned pointer which is derived from a parameter, such as a pointer into the
+        // passed buffer, is still owned by the caller of the function.
+        let returns_owned = body.local_decls[RETURN_LOCAL].is_linear()
+            || (!param_derived_locals(body).contains(&RETURN_LOCAL)
+                && linear_places
+                    .index_of_path(&PlacePath::root(RETURN_LOCAL))
+                    .is_some_and(|index| exit_state[index].status == LinearStatus::Owner));
+
+        Some((
+            ident.name.clone(),
+            FuncSummary {
+                params,
+                returns_owned,
+            },
+        ))
+    }
+}
+
+impl LinearCtx<'_> {
+    /// Returns the variables whose value reaches a sink, which is either a parameter which
+    /// takes the ownership of the value or a place reached through a pointer, directly or
+    /// through the variables which it is copied to.
+    fn sunk_locals(
+        &self,
+        body: &mir::Body,
+        stored_locals: &HashSet<mir::Local>,
+    ) -> HashSet<mir::Local> {
+        let mut sunk_locals = stored_locals.clone();
+
+        let statements = body
+            .basic_blocks
+            .iter()
+            .flat_map(|(_, bb_data)| &bb_data.statements);
+
+        for statement in statements.clone() {
+            let (mir::StatementKind::Assign(_, mir::Rvalue::Call(func, args))
+            | mir::StatementKind::Call(func, args)) = &statement.kind
+            else {
+                continue;
+            };
+
+            let func_sig = callee_sig(body, func);
+            let summary =
+                callee_name(body, func).and_then(|func_name| self.summaries.get(func_name));
+
+            for (position, arg) in args.iter().enumerate() {
+                let mir::Operand::Place(place) = arg else {
+                    continue;
+                };
+
+                let takes_ownership = func_sig
+                    .and_then(|func_sig| func_sig.params.get(position))
+                    .is_some_and(|param_decl| param_decl.ty.is_linear)
+                    || summary
+                        .and_then(|summary| summary.effect_of(position))
+                        .is_some_and(|effect| {
+                            matches!(effect, ParamEffect::Consumes | ParamEffect::Stores)
+                        });
+
+                if takes_ownership && place.projections.is_empty() {
+                    sunk_locals.insert(place.local);
+                }
+            }
+        }
+
+        // The value of a variable reaches the sinks of the variables which it is copied to.
+        loop {
+            let mut is_changed = false;
+
+            for statement in statements.clone() {
+                let mir::StatementKind::Assign(
+                    lhs,
+                    mir::Rvalue::Use(mir::Operand::Place(place))
+                    | mir::Rvalue::Cast {
+                        value: mir::Operand::Place(place),
+                        ..
+                    },
+                ) = &statement.kind
+                else {
+                    continue;
+                };
+
+                if lhs.projections.is_empty()
+                    && place.projections.is_empty()
+                    && sunk_locals.contains(&lhs.local)
+                {
+                    is_changed |= sunk_locals.insert(place.local);
+                }
+            }
+
+            if !is_changed {
+                break sunk_locals;
+            }
+        }
+    }
+}
+
+/// Returns the variables whose value is derived from the parameters of the function, such as
+/// the pointers which walk over a passed buffer or point into it.
+fn param_derived_locals(body: &mir::Body) -> HashSet<mir::Local> {
+    let mut derived_locals: HashSet<_> = body
+        .local_decls
+        .iter()
+        .filter(|(_, local_decl)| {
+            matches!(local_decl.kind, mir::LocalKind::Real { is_arg: true, .. })
+        })
+        .map(|(local, _)| local)
+        .collect();
+
+    loop {
+        let mut is_changed = false;
+
+        for (_, bb_data) in body.basic_blocks.iter() {
+            for statement in &bb_data.statements {
+                let mir::StatementKind::Assign(lhs, rvalue) = &statement.kind else {
+                    continue;
+                };
+
+                let read_operands = match rvalue {
+                    mir::Rvalue::Use(operand)
+                    | mir::Rvalue::UnaryOp(_, operand)
+                    | mir::Rvalue::Cast { value: operand, .. } => vec![operand],
+                    mir::Rvalue::BinaryOp(_, lhs_operand, rhs_operand) => {
+                        vec![lhs_operand, rhs_operand]
+                    }
+                    _ => vec![],
+                };
+
+                let mut read_locals = read_operands
+                    .into_iter()
+                    .filter_map(|operand| match operand {
+                        mir::Operand::Place(place) => Some(place.local),
+                        mir::Operand::Const(_) => None,
+                    })
+                    .chain(match rvalue {
+                        mir::Rvalue::AddrOf(place) => Some(place.local),
+                        _ => None,
+                    });
+
+                if lhs.projections.is_empty()
+                    && read_locals.any(|local| derived_locals.contains(&local))
+                {
+                    is_changed |= derived_locals.insert(lhs.local);
+                }
+            }
+        }
+
+        if !is_changed {
+            break derived_locals;
+        }
+    }
+}
+
+/// Returns the tracked variables which their value is stored in a place reached through a
+/// pointer, such as a global variable or a field of a heap allocated value.
+fn stored_locals(body: &mir::Body, linear_places: &LinearPlaces) -> HashSet<mir::Local> {
+    let mut stored_locals = HashSet::new();
+
+    for (_, bb_data) in body.basic_blocks.iter() {
+        for statement in &bb_data.statements {
+            let mir::StatementKind::Assign(lhs, rvalue) = &statement.kind else {
+                continue;
+            };
+
+            let (mir::Rvalue::Use(mir::Operand::Place(place))
+            | mir::Rvalue::Cast {
+                value: mir::Operand::Place(place),
+                ..
+            }) = rvalue
+            else {
+                continue;
+            };
+
+            if lhs
+                .projections
+                .iter()
+                .any(|projection| matches!(projection, mir::PlaceElem::Deref))
+                && place.projections.is_empty()
+                && linear_places.index_of(place).is_some()
+            {
+                stored_locals.insert(place.local);
+            }
+        }
+    }
+
+    stored_locals
+}
+
+/// Returns the signature of the function which is called directly by its name, either
+/// through its definition or through a declaration of it.
+fn callee_sig<'body>(body: &'body mir::Body, func: &mir::Operand) -> Option<&'body hir::FuncSig> {
+    let mir::Operand::Const(mir::Const::Symbol(symbol)) = func else {
+        return None;
+    };
+
+    match body.symbol_resolver.get_data_by_res(symbol) {
+        resolver::SymbolKind::Func(func_decl) => Some(&func_decl.sig),
+        resolver::SymbolKind::Var(var_decl) => match &var_decl.ty.kind {
+            hir::TyKind::Func { sig } => Some(sig),
+            hir::TyKind::Ptr { kind, .. } => match kind.as_ref() {
+                hir::TyKind::Func { sig } => Some(sig),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the name of the function which is called directly by its name, either through its
+/// definition or through a declaration of it.
+pub(crate) fn callee_name<'body>(
+    body: &'body mir::Body,
+    func: &mir::Operand,
+) -> Option<&'body str> {
+    let mir::Operand::Const(mir::Const::Symbol(symbol)) = func else {
+        return None;
+    };
+
+    match body.symbol_resolver.get_data_by_res(symbol) {
+        resolver::SymbolKind::Func(func_decl) => Some(&func_decl.ident.name),
+        resolver::SymbolKind::Var(var_decl) if var_decl.ty.kind.is_fn() => {
+            Some(&var_decl.ident.name)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,177 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use ast_utils::Comment;
+use azhdaha_errors::{Diagnostic, Location};
+use log::warn;
+
+use repr::hir::Span;
+
+use crate::linear::{LinearCtx, Violation, ViolationKind};
+
+/// The beginning of the comments which suppress diagnostics, such as
+/// `// azhdaha-allow(leak): reason`.
+const SUPPRESSION_PREFIX: &str = "azhdaha-allow(";
+
+/// The kinds of violations which are suppressed by the `leak` category.
+const LEAK_KINDS: [ViolationKind; 5] = [
+    ViolationKind::MemoryLeakage,
+    ViolationKind::MemoryLeakageAtExit,
+    ViolationKind::DroppedOwnedField,
+    ViolationKind::IgnoredLinearReturn,
+    ViolationKind::IgnoredLinearResult,
+];
+
+/// A comment which allows the violations of some kinds on a line, or in a function which
+/// starts at that line.
+#[derive(Debug)]
+struct Suppression {
+    kinds: Vec<ViolationKind>,
+    span: Span,
+    /// The line which the suppression applies to.
+    location: Location,
+    is_used: bool,
+}
+
+impl Suppression {
+    /// Whether the suppression is written for the function which contains the diagnostic or
+    /// for any of the lines which the diagnostic points at.
+    fn applies_to(&self, diagnostic: &Diagnostic) -> bool {
+        let is_on_line = |location: &Location| {
+            location.file == self.location.file && location.line == self.location.line
+        };
+
+        (diagnostic.location.file == self.location.file
+            && diagnostic.function_line == Some(self.location.line))
+            || is_on_line(&diagnostic.location)
+            || diagnostic
+                .labels
+                .iter()
+                .any(|label| is_on_line(&label.location))
+    }
+}
+
+impl LinearCtx<'_> {
+    /// Drops the diagnostics which are allowed by the suppression comments in the source code
+    /// and reports the suppressions which do not match any of the diagnostics.
+    pub fn suppress(&self, comments: &[Comment], diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let mut suppressions: Vec<_> = comments
+            .iter()
+            .filter_map(|comment| self.parse_suppression(comment, comments))
+            .collect();
+
+        let mut diagnostics: Vec<_> = diagnostics
+            .into_iter()
+            .filter(|diagnostic| {
+                let mut is_suppressed = false;
+
+                for suppression in &mut suppressions {
+                    let kind = diagnostic.code.and_then(ViolationKind::from_code);
+
+                    if kind.is_some_and(|kind| suppression.kinds.contains(&kind))
+                        && suppression.applies_to(diagnostic)
+                    {
+                        suppression.is_used = true;
+                        is_suppressed = true;
+                    }
+                }
+
+                !is_suppressed
+            })
+            .collect();
+
+        // The suppressions in the included headers are not reported, since the translation
+        // unit may not contain the diagnostics which they are written for while the others
+        // which include the same headers do.
+        for suppression in suppressions.iter().filter(|suppression| {
+            !suppression.is_used && self.locate(suppression.span).file == self.source_info.path
+        }) {
+            let violation = Violation::new(ViolationKind::UnusedSuppression, suppression.span)
+                .with_label(
+                    "This suppression does not match any diagnostic",
+                    suppression.span,
+                )
+                .with_help("Try to remove the suppression or move it to the reported line");
+
+            diagnostics.push(self.diagnose(&violation));
+        }
+
+        diagnostics
+    }
+
+    fn parse_suppression(&self, comment: &Comment, comments: &[Comment]) -> Option<Suppression> {
+        let (names, _reason) = comment
+            .text
+            .trim()
+            .strip_prefix(SUPPRESSION_PREFIX)?
+            .split_once(')')?;
+
+        let mut kinds = vec![];
+
+        for name in names.split(',').map(str::trim) {
+            match kinds_of(name) {
+                Some(named_kinds) => kinds.extend(named_kinds),
+                None => warn!("Unknown kind '{name}' in the suppression comment."),
+            }
+        }
+
+        let span = Span {
+            lo: comment.lo,
+            hi: comment.hi,
+        };
+
+        Some(Suppression {
+            kinds,
+            span,
+            location: self.locate(self.suppressed_span(comment, comments)),
+            is_used: false,
+        })
+    }
+
+    /// Returns the span which the comment applies to, which is the comment itself when it
+    /// follows code on its line, or otherwise the next code after it.
+    fn suppressed_span(&self, comment: &Comment, comments: &[Comment]) -> Span {
+        let line_start = self.source_code[..comment.lo]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+
+        if !self.source_code[line_start..comment.lo].trim().is_empty() {
+            return Span {
+                lo: comment.lo,
+                hi: comment.hi,
+            };
+        }
+
+        let mut position = comment.hi;
+
+        loop {
+            position += self.source_code[position..]
+                .find(|char: char| !char.is_whitespace())
+                .unwrap_or(self.source_code.len() - position);
+
+            match comments.iter().find(|other| other.lo == position) {
+                Some(other) => position = other.hi,
+                None => break,
+            }
+        }
+
+        Span {
+            lo: position,
+            hi: position,
+        }
+    }
+}
+
+/// Returns the kinds of violations which are named by a category, a code or a rule id.
+fn kinds_of(name: &str) -> Option<Vec<ViolationKind>> {
+    if name == "leak" {
+        return Some(LEAK_KINDS.to_vec());
+    }
+
+    ViolationKind::from_code(name)
+        .or_else(|| {
+            ViolationKind::ALL
+                .into_iter()
+                .find(|kind| kind.rule_id() == name)
+        })
+        .map(|kind| vec![kind])
+}
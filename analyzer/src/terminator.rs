@@ -1,96 +1,124 @@
 #![allow(clippy::missing_docs_in_private_items)]
 
-use ariadne::{Fmt as _, Label, ReportBuilder};
+use repr::mir;
 
-use repr::mir::{self, RETURN_LOCAL};
-
-use crate::{
-    DIAGNOSIS_REPORT_COLOR, LinearCtx,
-    linear::{LinearLocal, LinearStatus},
-    report::ReportSpan,
-};
+use crate::linear::{LinearCtx, LinearPlaces, LinearState, LinearStatus, Violation, ViolationKind};
 
 impl LinearCtx<'_> {
     pub(crate) fn process_terminator(
         &self,
-        report_builder: &mut ReportBuilder<'_, ReportSpan>,
-        linear_local: &mut LinearLocal,
+        body: &mir::Body,
+        linear_places: &LinearPlaces,
+        state: &mut LinearState,
         terminator: &Option<mir::Terminator>,
-    ) -> anyhow::Result<bool> {
-        match linear_local.status {
-            LinearStatus::Owner => {
-                if linear_local.local == RETURN_LOCAL {
-                    return Ok(false);
-                }
+        violations: &mut Vec<Violation>,
+    ) {
+        let is_exit = match terminator.as_ref().map(|terminator| &terminator.kind) {
+            Some(mir::TerminatorKind::Return) | None => false,
+            Some(mir::TerminatorKind::Unreachable) => true,
+            Some(mir::TerminatorKind::Goto { .. } | mir::TerminatorKind::SwitchInt { .. }) => {
+                return;
+            }
+        };
 
-                match terminator {
-                    Some(terminator) => {
-                        if !matches!(&terminator.kind, mir::TerminatorKind::Return) {
-                            return Ok(false);
-                        }
+        if is_exit && self.allow_exit_leaks {
+            return;
+        }
 
-                        report_builder.set_message("Memory leakage after return");
+        let span = match terminator {
+            Some(terminator) => terminator.span,
+            None => body.span,
+        };
 
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(terminator.span))
-                                .with_message(format!(
-                                    "Function returns in here without {} moving its value",
-                                    format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                                ))
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
-                    }
-                    None => {
-                        report_builder.set_message("Memory leakage after return");
+        for (index, linear_place) in linear_places.iter() {
+            // Nothing is returned to the caller when the program exits.
+            if is_exit && linear_place.is_return() {
+                continue;
+            }
 
-                        report_builder.add_note(format!(
-                            "Function ends without {} moving its value",
-                            format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                        ));
-                    }
-                }
+            let linear_value = &state[index];
 
-                report_builder.add_help(format!(
-                    "Try to move {}'s value before reaching the return",
-                    format!("`{}`", linear_local.name).fg(DIAGNOSIS_REPORT_COLOR)
-                ));
+            // Fields reached through a pointer are owned by the pointed value rather than
+            // the function itself.
+            let is_violated = match linear_value.status {
+                LinearStatus::Owner => !linear_place.is_return() && linear_place.is_in_frame(),
+                LinearStatus::Free => linear_place.is_return(),
+                LinearStatus::MaybeOwned => linear_place.is_return() || linear_place.is_in_frame(),
+                LinearStatus::Unknown => false,
+            };
 
-                Ok(true)
+            if !is_violated {
+                continue;
             }
-            LinearStatus::Free => {
-                if linear_local.local != RETURN_LOCAL {
-                    return Ok(false);
-                }
-
-                match terminator {
-                    Some(terminator) => {
-                        if !matches!(&terminator.kind, mir::TerminatorKind::Return) {
-                            return Ok(false);
-                        }
 
-                        report_builder.set_message("Ignored linear return type");
+            let violation = if is_exit {
+                Violation::of_place(
+                    ViolationKind::MemoryLeakageAtExit,
+                    span,
+                    linear_places,
+                    index,
+                    linear_value,
+                )
+                .with_label(
+                    format!(
+                        "Program exits in here without {} moving its value",
+                        linear_place.quoted_name()
+                    ),
+                    span,
+                )
+                .with_help(format!(
+                    "Try to move {}'s value before exiting the program",
+                    linear_place.quoted_name()
+                ))
+            } else if linear_place.is_return() {
+                let violation = Violation::of_place(
+                    ViolationKind::IgnoredLinearReturn,
+                    span,
+                    linear_places,
+                    index,
+                    linear_value,
+                );
 
-                        report_builder.add_label(
-                            Label::new(ReportSpan::new(terminator.span))
-                                .with_message(
-                                    "Function returns in here without returning a linear variable",
-                                )
-                                .with_color(DIAGNOSIS_REPORT_COLOR),
-                        );
-                    }
+                match terminator {
+                    Some(_) => violation.with_label(
+                        "Function returns in here without returning a linear variable",
+                        span,
+                    ),
                     None => {
-                        report_builder.set_message("Ignored linear return type");
-
-                        report_builder
-                            .add_note("Function ends without returning a linear variable");
+                        violation.with_note("Function ends without returning a linear variable")
                     }
                 }
+                .with_help("Try to return a linear variable")
+            } else {
+                let violation = Violation::of_place(
+                    ViolationKind::MemoryLeakage,
+                    span,
+                    linear_places,
+                    index,
+                    linear_value,
+                );
 
-                report_builder.add_help("Try to return a linear variable");
+                match terminator {
+                    Some(_) => violation.with_label(
+                        format!(
+                            "Function returns in here without {} moving its value",
+                            linear_place.quoted_name()
+                        ),
+                        span,
+                    ),
+                    None => violation.with_note(format!(
+                        "Function ends without {} moving its value",
+                        linear_place.quoted_name()
+                    )),
+                }
+                .with_help(format!(
+                    "Try to move {}'s value before reaching the return",
+                    linear_place.quoted_name()
+                ))
+            };
 
-                Ok(true)
-            }
-            LinearStatus::Unknown => Ok(false),
+            violations.push(violation);
+            state[index].reset();
         }
     }
 }
@@ -3,5 +3,6 @@
 #define _AZHDAHA_H	1
 
 #define LINEAR_TYPE
+#define LINEAR_BORROW
 
 #endif /* azhdaha.h  */
@@ -3,5 +3,6 @@
 #define _AZHDAHA_H	1
 
 #define LINEAR_TYPE linear_type
+#define LINEAR_BORROW __attribute__((borrowed_type))
 
 #endif /* azhdaha.h  */
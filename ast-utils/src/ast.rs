@@ -1,11 +1,16 @@
-use std::{fs::File, os::fd::AsRawFd};
+use std::{fs::File, os::fd::AsRawFd, path::Path};
 
 use anyhow::{Context, bail};
+use azhdaha_errors::Span;
 use compile_commands::CompilationDatabase;
 use log::error;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use tree_sitter::{Parser, Tree};
 
-use crate::preprocess::{SourceInfo, preprocess};
+use crate::preprocess::{SourceInfo, preprocess, preprocess_path, preprocess_source};
+
+/// The kind of the nodes of the function definitions.
+const FUNCTION_DEFINITION: &str = "function_definition";
 
 pub struct AstRepr {
     /// The source code which the tree has been generated from.
@@ -24,32 +29,69 @@ impl AstRepr {
     }
 
     pub fn new_single_file(text: &str) -> anyhow::Result<Self> {
-        let source_info = SourceInfo {
-            path: "main.c".to_owned(),
-            code: text.as_bytes().to_vec(),
-        };
+        let source_info = SourceInfo::new(
+            "main.c".to_owned(),
+            Path::new("."),
+            text.as_bytes().to_vec(),
+        );
 
         let mut parser = Parser::new();
         parser
             .set_language(&tree_sitter_c::LANGUAGE.into())
             .context("Failed to load C grammar.")?;
 
-        let Some(tree) = parser.parse(&source_info.code, None) else {
+        let Some(tree) = parser.parse(source_info.parsable_code(), None) else {
             bail!("Failed to parse using tree-sitter.");
         };
         Ok(AstRepr { source_info, tree })
     }
 
-    pub fn construct(compile_commands: &CompilationDatabase) -> anyhow::Result<Vec<Self>> {
-        let mut parser = Parser::new();
-        parser
+    /// Preprocesses and parses the source code which is given as a string, while passing the
+    /// flags to the preprocessor. The diagnostics of the code are reported at the path.
+    pub fn from_source(path: &str, code: &str, flags: &[String]) -> anyhow::Result<Self> {
+        Self::parse(preprocess_source(path, code, flags)?)
+    }
+
+    /// Preprocesses and parses the source code of the file by the entry of compile-commands
+    /// which applies to it, which is [`None`] when no entry applies to it.
+    pub fn from_compile_commands(
+        compile_commands: &CompilationDatabase,
+        path: &Path,
+        code: &str,
+    ) -> Option<anyhow::Result<Self>> {
+        preprocess_path(compile_commands, path, code).map(|source_info| Self::parse(source_info?))
+    }
+
+    /// Preprocesses and parses the entries of compile-commands, along with the number of
+    /// entries which failed to be preprocessed or parsed.
+    pub fn construct(compile_commands: &CompilationDatabase) -> anyhow::Result<(Vec<Self>, u32)> {
+        // The grammar is loaded once beforehand, so the parsers of the threads cannot fail to
+        // load it.
+        Parser::new()
             .set_language(&tree_sitter_c::LANGUAGE.into())
             .context("Failed to load C grammar.")?;
 
+        let (source_infos, mut error_count) = preprocess(compile_commands);
+
+        // Each of the threads parses the entries by its own parser, and the trees are collected
+        // in the order of the entries.
+        let trees: Vec<_> = source_infos
+            .par_iter()
+            .map_init(
+                || {
+                    let mut parser = Parser::new();
+                    parser.set_language(&tree_sitter_c::LANGUAGE.into()).ok();
+                    parser
+                },
+                |parser, source_info| parser.parse(source_info.parsable_code(), None),
+            )
+            .collect();
+
         let mut asts = vec![];
 
-        for source_info in preprocess(compile_commands)? {
-            let Some(tree) = parser.parse(&source_info.code, None) else {
+        for (source_info, tree) in source_infos.into_iter().zip(trees) {
+            let Some(tree) = tree else {
+                error_count += 1;
                 error!("Failed to parse using tree-sitter.");
                 continue;
             };
@@ -57,6 +99,34 @@ impl AstRepr {
             asts.push(AstRepr { source_info, tree });
         }
 
-        Ok(asts)
+        Ok((asts, error_count))
+    }
+
+    /// Returns the spans of the function definitions at the top level of the source code.
+    pub fn func_def_spans(&self) -> Vec<Span> {
+        let root = self.tree.root_node();
+        let mut cursor = root.walk();
+
+        root.children(&mut cursor)
+            .filter(|node| node.kind() == FUNCTION_DEFINITION)
+            .map(|node| Span {
+                lo: node.start_byte(),
+                hi: node.end_byte(),
+            })
+            .collect()
+    }
+
+    /// Parses the preprocessed source code.
+    pub fn parse(source_info: SourceInfo) -> anyhow::Result<Self> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_c::LANGUAGE.into())
+            .context("Failed to load C grammar.")?;
+
+        let Some(tree) = parser.parse(source_info.parsable_code(), None) else {
+            bail!("Failed to parse using tree-sitter.");
+        };
+
+        Ok(AstRepr { source_info, tree })
     }
 }
@@ -0,0 +1,117 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+/// A comment which is kept in the source code by the preprocessor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub lo: usize,
+    pub hi: usize,
+    /// The text of the comment without its delimiters.
+    pub text: String,
+}
+
+impl Comment {
+    /// Finds the comments in the source code while skipping the string and the character
+    /// literals.
+    pub(crate) fn find_all(code: &[u8]) -> Vec<Self> {
+        let mut comments = vec![];
+        let mut index = 0;
+
+        while index < code.len() {
+            match (code[index], code.get(index + 1)) {
+                (b'/', Some(b'/')) => {
+                    let hi = code[index..]
+                        .iter()
+                        .position(|byte| *byte == b'\n')
+                        .map_or(code.len(), |offset| index + offset);
+
+                    comments.push(Self::new(code, index, hi, index + 2, hi));
+                    index = hi;
+                }
+                (b'/', Some(b'*')) => {
+                    // An unterminated comment continues until the end of the source code.
+                    let (text_hi, hi) = code[index + 2..]
+                        .windows(2)
+                        .position(|window| window == b"*/")
+                        .map_or((code.len(), code.len()), |offset| {
+                            (index + 2 + offset, index + 2 + offset + 2)
+                        });
+
+                    comments.push(Self::new(code, index, hi, index + 2, text_hi));
+                    index = hi;
+                }
+                (quote @ (b'"' | b'\''), _) => {
+                    index += 1;
+
+                    while index < code.len() && code[index] != quote && code[index] != b'\n' {
+                        if code[index] == b'\\' {
+                            index += 1;
+                        }
+
+                        index += 1;
+                    }
+
+                    index += 1;
+                }
+                _ => index += 1,
+            }
+        }
+
+        comments
+    }
+
+    fn new(code: &[u8], lo: usize, hi: usize, text_lo: usize, text_hi: usize) -> Self {
+        Self {
+            lo,
+            hi,
+            text: String::from_utf8_lossy(&code[text_lo..text_hi]).into_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Comment;
+
+    fn texts(code: &str) -> Vec<String> {
+        Comment::find_all(code.as_bytes())
+            .into_iter()
+            .map(|comment| comment.text)
+            .collect()
+    }
+
+    #[test]
+    fn finds_the_line_and_the_block_comments() {
+        let code = "int a; // line\n/* block\n */ int b;";
+        let comments = Comment::find_all(code.as_bytes());
+
+        assert_eq!(
+            comments,
+            [
+                Comment {
+                    lo: 7,
+                    hi: 14,
+                    text: " line".to_owned(),
+                },
+                Comment {
+                    lo: 15,
+                    hi: 27,
+                    text: " block\n ".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_the_string_and_the_character_literals() {
+        assert_eq!(texts(r#"char *s = "// /* \" //"; // real"#), [" real"]);
+        assert_eq!(texts("char c = '\\''; /* real */"), [" real "]);
+        assert_eq!(texts("char c = '/'; char d = '*'; // real"), [" real"]);
+    }
+
+    #[test]
+    fn ends_the_unterminated_literals_and_comments() {
+        // An unterminated literal ends at the end of its line.
+        assert_eq!(texts("char *s = \"abc\n// real"), [" real"]);
+        assert_eq!(texts("int a; /* unterminated"), [" unterminated"]);
+    }
+}
@@ -0,0 +1,225 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::ops::Range;
+
+use anyhow::{Context, bail};
+use azhdaha_errors::Span;
+use tree_sitter::{InputEdit, Parser, Point};
+
+use crate::{ast::AstRepr, preprocess::SourceInfo};
+
+/// The range of the preprocessed source code which is replaced by an edit, where the offsets
+/// before the range stay the same and the offsets after it are shifted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceEdit {
+    pub start: usize,
+    /// The end of the replaced range in the source code before the edit.
+    pub old_end: usize,
+    /// The end of the replacing text in the source code after the edit.
+    pub new_end: usize,
+}
+
+impl SourceEdit {
+    /// Finds the smallest edit which turns the old source code into the new one.
+    pub fn between(old_code: &[u8], new_code: &[u8]) -> Self {
+        let prefix = old_code
+            .iter()
+            .zip(new_code)
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        let suffix = old_code[prefix..]
+            .iter()
+            .rev()
+            .zip(new_code[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        Self {
+            start: prefix,
+            old_end: old_code.len() - suffix,
+            new_end: new_code.len() - suffix,
+        }
+    }
+
+    /// Whether the edit does not change the source code.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.old_end && self.start == self.new_end
+    }
+
+    /// Whether the edit is inside the span of the source code before the edit.
+    pub fn is_inside(&self, span: Span) -> bool {
+        span.lo <= self.start && self.old_end <= span.hi
+    }
+
+    /// Maps the span of the source code before the edit to the source code after it, which
+    /// is [`None`] when the span overlaps the replaced range.
+    pub fn map(&self, span: Span) -> Option<Span> {
+        if self.is_empty() || span.hi <= self.start {
+            Some(span)
+        } else if span.lo >= self.old_end {
+            Some(Span {
+                lo: span.lo - self.old_end + self.new_end,
+                hi: span.hi - self.old_end + self.new_end,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn to_input_edit(self, old_code: &[u8], new_code: &[u8]) -> InputEdit {
+        InputEdit {
+            start_byte: self.start,
+            old_end_byte: self.old_end,
+            new_end_byte: self.new_end,
+            start_position: point_at(old_code, self.start),
+            old_end_position: point_at(old_code, self.old_end),
+            new_end_position: point_at(new_code, self.new_end),
+        }
+    }
+}
+
+impl AstRepr {
+    /// Replaces the source code with the newly preprocessed one, such as after its file is
+    /// changed, and reparses only the parts of the tree which are changed. Returns the edit
+    /// which turned the old source code into the new one.
+    pub fn update(&mut self, source_info: SourceInfo) -> anyhow::Result<SourceEdit> {
+        let edit = SourceEdit::between(&self.source_info.code, &source_info.code);
+
+        if edit.is_empty() {
+            self.source_info = source_info;
+
+            return Ok(edit);
+        }
+
+        self.tree
+            .edit(&edit.to_input_edit(&self.source_info.code, &source_info.code));
+
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_c::LANGUAGE.into())
+            .context("Failed to load C grammar.")?;
+
+        let Some(tree) = parser.parse(source_info.parsable_code(), Some(&self.tree)) else {
+            bail!("Failed to parse using tree-sitter.");
+        };
+
+        self.tree = tree;
+        self.source_info = source_info;
+
+        Ok(edit)
+    }
+
+    /// Replaces the range of the preprocessed source code with the text and reparses only the
+    /// parts of the tree which are changed.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) -> anyhow::Result<SourceEdit> {
+        if range.start > range.end || range.end > self.source_info.code.len() {
+            bail!("The range of the edit is out of the source code.");
+        }
+
+        let mut code = self.source_info.code.clone();
+        code.splice(range, text.bytes());
+
+        let source_info = SourceInfo::new(
+            self.source_info.path.clone(),
+            &self.source_info.directory,
+            code,
+        );
+
+        self.update(source_info)
+    }
+}
+
+/// Returns the row and the column in bytes of the offset, both starting from zero.
+fn point_at(code: &[u8], offset: usize) -> Point {
+    let before = &code[..offset];
+
+    let row = before.iter().filter(|byte| **byte == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |index| index + 1);
+
+    Point::new(row, offset - line_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use azhdaha_errors::Span;
+    use tree_sitter::Point;
+
+    use super::{SourceEdit, point_at};
+    use crate::ast::AstRepr;
+
+    fn edit(start: usize, old_end: usize, new_end: usize) -> SourceEdit {
+        SourceEdit {
+            start,
+            old_end,
+            new_end,
+        }
+    }
+
+    #[test]
+    fn finds_the_smallest_edit() {
+        assert_eq!(SourceEdit::between(b"int a;", b"int a;"), edit(6, 6, 6));
+        assert_eq!(SourceEdit::between(b"int a;", b"int ab;"), edit(5, 5, 6));
+        assert_eq!(SourceEdit::between(b"int ab;", b"int a;"), edit(5, 6, 5));
+        assert_eq!(SourceEdit::between(b"int a;", b"long a;"), edit(0, 3, 4));
+        // The common prefix and suffix do not overlap when the characters are repeated.
+        assert_eq!(SourceEdit::between(b"aa", b"aaa"), edit(2, 2, 3));
+        assert_eq!(SourceEdit::between(b"aaa", b"aa"), edit(2, 3, 2));
+    }
+
+    #[test]
+    fn maps_the_spans_around_the_edit() {
+        let edit = edit(10, 14, 20);
+
+        assert_eq!(
+            edit.map(Span { lo: 2, hi: 10 }),
+            Some(Span { lo: 2, hi: 10 })
+        );
+        assert_eq!(
+            edit.map(Span { lo: 14, hi: 16 }),
+            Some(Span { lo: 20, hi: 22 })
+        );
+        assert_eq!(edit.map(Span { lo: 8, hi: 11 }), None);
+        assert_eq!(edit.map(Span { lo: 13, hi: 16 }), None);
+
+        // No span overlaps an empty edit.
+        assert_eq!(
+            SourceEdit::between(b"a", b"a").map(Span { lo: 0, hi: 1 }),
+            Some(Span { lo: 0, hi: 1 })
+        );
+    }
+
+    #[test]
+    fn checks_whether_the_edit_is_inside_the_span() {
+        let edit = edit(10, 14, 20);
+
+        assert!(edit.is_inside(Span { lo: 10, hi: 14 }));
+        assert!(edit.is_inside(Span { lo: 0, hi: 30 }));
+        assert!(!edit.is_inside(Span { lo: 11, hi: 30 }));
+        assert!(!edit.is_inside(Span { lo: 0, hi: 13 }));
+    }
+
+    #[test]
+    fn finds_the_points_of_the_offsets() {
+        let code = b"int a;\nint b;\n";
+
+        assert_eq!(point_at(code, 0), Point::new(0, 0));
+        assert_eq!(point_at(code, 11), Point::new(1, 4));
+        assert_eq!(point_at(code, code.len()), Point::new(2, 0));
+    }
+
+    #[test]
+    fn reparses_the_edited_source_code() {
+        let mut ast_repr = AstRepr::new_single_file("int a;\nint b;\n").unwrap();
+
+        let source_edit = ast_repr.edit(11..12, "count").unwrap();
+
+        assert_eq!(source_edit, edit(11, 12, 16));
+        assert_eq!(ast_repr.source_info.code, b"int a;\nint count;\n");
+        assert!(!ast_repr.tree.root_node().has_error());
+        assert!(ast_repr.edit(4..100, "x").is_err());
+    }
+}
@@ -4,7 +4,17 @@
 
 /// Contains [`AstRepr`]s properties.
 mod ast;
+/// Contains the comments which are kept in the source code after preprocessing.
+mod comment;
+/// Contains the edits of the source code which the trees are reparsed incrementally after.
+mod edit;
 /// Contains functions used for preprocessing source code.
 mod preprocess;
+/// Contains the mapping from the preprocessed source code to the original files.
+mod source_map;
 
 pub use ast::AstRepr;
+pub use comment::Comment;
+pub use edit::SourceEdit;
+pub use preprocess::{SourceInfo, annotated_lib_dir, preprocess_path, preprocess_source};
+pub use source_map::{SourceMap, SourcePosition};
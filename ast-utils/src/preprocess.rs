@@ -1,17 +1,57 @@
-use std::process::Command;
+use std::{
+    fs,
+    io::{self, Write},
+    iter,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
 
-use compile_commands::{CompilationDatabase, CompileArgs, SourceFile};
-use log::warn;
+use anyhow::{Context, anyhow, bail};
+use azhdaha_errors::{Location, Span};
+use compile_commands::{CompilationDatabase, CompileArgs, CompileCommand, SourceFile};
+use log::error;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    comment::Comment,
+    source_map::{SourceMap, SourcePosition},
+};
 
 /// Indicates that only preprocess phase should be done.
 const PREPROCESS_ONLY_FLAG: &str = "-E";
 
-/// Inhibits generation of linemarkers in the output from the preprocessor.
-const INHABIT_LINEMARKS_FLAG: &str = "-P";
+/// Keeps the comments in the output from the preprocessor.
+const KEEP_COMMENTS_FLAG: &str = "-C";
+
+/// The compiler which runs the preprocessor for the entries which do not name one, such as
+/// the entries read from compile-flags.
+const DEFAULT_COMPILER: &str = "cc";
+
+/// The flags of the compiler which conflict with [`PREPROCESS_ONLY_FLAG`] or remove the
+/// linemarkers, along with whether each of them takes a value.
+const CONFLICTING_FLAGS: [(&str, bool); 13] = [
+    ("-o", true),
+    ("-c", false),
+    ("-S", false),
+    ("-P", false),
+    ("-M", false),
+    ("-MM", false),
+    ("-MD", false),
+    ("-MMD", false),
+    ("-MG", false),
+    ("-MP", false),
+    ("-MF", true),
+    ("-MT", true),
+    ("-MQ", true),
+];
 
 /// Includes annotated headers.
 const INCLUDE_FLAG: &str = "-I";
 
+/// Makes the compiler read C source code from its standard input.
+const STDIN_SOURCE_FLAGS: [&str; 3] = ["-x", "c", "-"];
+
 /// The directory which contains the annotated headers.
 const ANNOTATED_LIB_PATH: &str = "include/azhdaha/";
 
@@ -20,11 +60,131 @@ const ANNOTATED_LIB_PATH: &str = "include/azhdaha/";
 const UNDEF_FLAG: &str = "-undef";
 
 /// Contains the source information about the code.
+#[derive(Debug)]
 pub struct SourceInfo {
     /// Path to the target file.
     pub path: String,
+    /// The directory which the source code was preprocessed in.
+    pub directory: PathBuf,
     /// Preprocessed source code.
     pub code: Vec<u8>,
+    /// The comments which are kept in the preprocessed source code.
+    pub comments: Vec<Comment>,
+    /// The mapping from the preprocessed source code to the original files.
+    pub source_map: SourceMap,
+}
+
+impl SourceInfo {
+    /// Creates the source information while collecting the comments and the linemarkers of
+    /// the source code, which was preprocessed in the given directory.
+    pub(crate) fn new(path: String, directory: &Path, code: Vec<u8>) -> Self {
+        let comments = Comment::find_all(&code);
+        let source_map = SourceMap::new(&path, directory, &code);
+
+        Self {
+            path,
+            directory: directory.to_owned(),
+            code,
+            comments,
+            source_map,
+        }
+    }
+
+    /// Returns the position in the original files which the offset of the preprocessed
+    /// source code is expanded from.
+    pub fn locate(&self, offset: usize) -> SourcePosition<'_> {
+        self.source_map.resolve(&self.path, &self.code, offset)
+    }
+
+    /// Returns the offset of the preprocessed source code which the position in one of the
+    /// original files is expanded to.
+    pub fn offset_of(&self, position: SourcePosition<'_>) -> Option<usize> {
+        self.source_map.offset_of(&self.path, &self.code, position)
+    }
+
+    /// Returns the files which the preprocessed source code is expanded from, which are the
+    /// file of the entry and the headers which it includes.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.source_map
+            .files(&self.path)
+            .filter(|file| !file.starts_with('<'))
+            .map(|file| self.directory.join(file))
+            .collect()
+    }
+
+    /// Returns the offset of the end of the line which the offset is in.
+    pub fn line_end(&self, offset: usize) -> usize {
+        self.source_map.line_end(&self.code, offset)
+    }
+
+    /// Returns the source code in which the comments and the linemarkers are replaced with
+    /// whitespaces, so the parser does not encounter them while the positions of the rest of
+    /// the source code stay the same.
+    pub(crate) fn parsable_code(&self) -> Vec<u8> {
+        let mut code = self.code.clone();
+
+        let comment_ranges = self.comments.iter().map(|comment| (comment.lo, comment.hi));
+
+        for (lo, hi) in comment_ranges.chain(self.source_map.marker_ranges.iter().copied()) {
+            for byte in &mut code[lo..hi] {
+                if *byte != b'\n' {
+                    *byte = b' ';
+                }
+            }
+        }
+
+        code
+    }
+}
+
+impl azhdaha_errors::Source for SourceInfo {
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    fn location(&self, span: Span) -> Location {
+        let start = self.locate(span.lo);
+
+        // The range is cut at the end of its first line when it ends in another file, such as
+        // a range which contains an expanded header.
+        let end = match self.locate(span.hi) {
+            end if end.file == start.file && end.line >= start.line => end,
+            _ => self.locate(self.line_end(span.lo).max(span.lo)),
+        };
+
+        let file_offset = |position| {
+            self.source_map
+                .file_offset(&self.path, &self.directory, position)
+        };
+        let file_column = |position| {
+            self.source_map
+                .file_column(&self.path, &self.directory, position)
+        };
+
+        Location {
+            file: start.file.to_owned(),
+            line: start.line,
+            column: file_column(start),
+            end_line: end.line,
+            end_column: file_column(end),
+            lo: file_offset(start),
+            hi: file_offset(end),
+            span,
+        }
+    }
+
+    fn snippet(&self, span: Span) -> String {
+        let lo = span.lo.min(self.code.len());
+        let hi = span.hi.clamp(lo, self.line_end(lo));
+
+        String::from_utf8_lossy(&self.code[lo..hi])
+            .trim()
+            .to_owned()
+    }
 }
 
 /// Replace headers with annotated versions and expand macros.
@@ -38,65 +198,432 @@ pub struct SourceInfo {
 /// The `LINEAR_TYPE` macro used in the source code must be replaced by `linear_type` which is
 /// accomplished by inserting [`INCLUDE_FLAG`] and redefining the macro.
 ///
-pub(crate) fn preprocess(
-    compile_commands: &CompilationDatabase,
-) -> anyhow::Result<Vec<SourceInfo>> {
-    let mut results = vec![];
+/// The comments are kept by inserting [`KEEP_COMMENTS_FLAG`], since the suppressions of the
+/// diagnostics are written in them. The linemarkers are kept as well, in order to map the
+/// preprocessed source code back to the original files.
+///
+pub(crate) fn preprocess(compile_commands: &CompilationDatabase) -> (Vec<SourceInfo>, u32) {
+    // The entries are preprocessed in parallel, but the results are collected in the order of
+    // the entries, so the output does not depend on the scheduling.
+    let results: Vec<_> = compile_commands
+        .par_iter()
+        .map(|compile_command| {
+            let source_files = source_files(compile_command)?;
 
-    for compile_command in compile_commands {
-        let Some(directory) = compile_command.directory.to_str() else {
-            warn!("UTF-8 validity for directory section failed.");
-            continue;
-        };
+            Ok(source_files
+                .par_iter()
+                .map(|source_file| preprocess_file(compile_command, source_file))
+                .collect::<Vec<_>>())
+        })
+        .collect::<Vec<anyhow::Result<_>>>();
+
+    let mut source_infos = vec![];
+    let mut error_count = 0;
 
-        if !compile_command.directory.exists() {
-            warn!("Directory '{directory}' does not exist.");
-            continue;
+    for result in results.into_iter().flat_map(|result| match result {
+        Ok(results) => results,
+        Err(error) => vec![Err(error)],
+    }) {
+        match result {
+            Ok(source_info) => source_infos.push(source_info),
+            Err(error) => {
+                error_count += 1;
+                error!("Failed to preprocess an entry of compile-commands - {error:?}");
+            }
         }
+    }
+
+    (source_infos, error_count)
+}
+
+/// Preprocesses the source code of the file by the flags of the first entry of
+/// compile-commands which applies to it, which is [`None`] when no entry applies to it. The
+/// code is given as a string, such as the unsaved text of the file in an editor, and is named
+/// by the given path rather than the path written in the entry.
+pub fn preprocess_path(
+    compile_commands: &CompilationDatabase,
+    path: &Path,
+    code: &str,
+) -> Option<anyhow::Result<SourceInfo>> {
+    let path_str = path.to_str()?;
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
 
-        let path = match &compile_command.file {
+    let compile_command = compile_commands.iter().find(|compile_command| {
+        let directory = fs::canonicalize(&compile_command.directory)
+            .unwrap_or_else(|_| compile_command.directory.clone());
+
+        match &compile_command.file {
+            SourceFile::File(file) => {
+                fs::canonicalize(directory.join(file)).is_ok_and(|file| file == path)
+            }
             SourceFile::All => {
-                warn!("File section was not found.");
-                continue;
+                path.starts_with(&directory)
+                    && path.extension().is_some_and(|extension| extension == "c")
             }
-            SourceFile::File(path) => {
-                let Some(path) = path.to_str() else {
-                    warn!("UTF-8 validity for file section failed.");
-                    continue;
-                };
+        }
+    })?;
 
-                path.to_owned()
-            }
-        };
+    Some(
+        compiler_args(compile_command).and_then(|(compiler, mut args)| {
+            // The file itself is replaced by the code, while the headers next to it are still
+            // found as they are by the compiler.
+            args.retain(|arg| {
+                fs::canonicalize(compile_command.directory.join(arg))
+                    .map_or(true, |arg| arg != path)
+            });
+            args.push(INCLUDE_FLAG.to_owned());
+            args.push(
+                path.parent()
+                    .unwrap_or(Path::new("."))
+                    .to_string_lossy()
+                    .into_owned(),
+            );
 
-        let Some(args) = compile_command.arguments.as_ref() else {
-            warn!("Arguments section was not found.");
-            continue;
-        };
+            preprocess_stdin(&compiler, &args, &compile_command.directory, path_str, code)
+        }),
+    )
+}
+
+/// Returns the files which the entry applies to, which are all of the C source files in its
+/// directory when the entry is not specific to a file, such as the entries read from
+/// compile-flags.
+fn source_files(compile_command: &CompileCommand) -> anyhow::Result<Vec<SourceFileKind>> {
+    if !compile_command.directory.exists() {
+        bail!(
+            "Directory '{}' does not exist.",
+            compile_command.directory.display()
+        );
+    }
+
+    match &compile_command.file {
+        SourceFile::File(path) => Ok(vec![SourceFileKind::Listed(
+            path.to_str()
+                .context("UTF-8 validity for file section failed.")?
+                .to_owned(),
+        )]),
+        SourceFile::All => {
+            let mut paths = vec![];
+
+            find_c_sources(&compile_command.directory, &mut paths)
+                .context("Failed to find the source files of the directory.")?;
+
+            paths.sort();
+
+            paths
+                .into_iter()
+                .map(|path| {
+                    Ok(SourceFileKind::Found(
+                        path.to_str()
+                            .context("UTF-8 validity for the path of a source file failed.")?
+                            .to_owned(),
+                    ))
+                })
+                .collect()
+        }
+    }
+}
+
+/// A file which an entry of compile-commands applies to.
+enum SourceFileKind {
+    /// The file which is named by the entry and is passed to the compiler by its arguments.
+    Listed(String),
+    /// A file which is found in the directory of the entry and should be passed to the
+    /// compiler along with its flags.
+    Found(String),
+}
+
+/// Collects the C source files in the directory and its subdirectories.
+fn find_c_sources(directory: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            find_c_sources(&path, paths)?;
+        } else if path.extension().is_some_and(|extension| extension == "c") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the compiler and the arguments of the entry, without the flags which conflict
+/// with preprocessing.
+fn compiler_args(compile_command: &CompileCommand) -> anyhow::Result<(String, Vec<String>)> {
+    let args = match (&compile_command.arguments, &compile_command.command) {
+        (Some(CompileArgs::Arguments(args)), _) => args.clone(),
+        // Compile-flags do not name the compiler.
+        (Some(CompileArgs::Flags(flags)), _) => iter::once(DEFAULT_COMPILER.to_owned())
+            .chain(flags.iter().cloned())
+            .collect(),
+        (None, Some(command)) => {
+            shlex::split(command).context("Failed to split the command section.")?
+        }
+        (None, None) => bail!("Neither arguments nor command section was found."),
+    };
 
-        let (command, args) = match args {
-            CompileArgs::Arguments(args) => args.split_first().unwrap(),
-            CompileArgs::Flags(_) => {
-                // Arguments might be read from compile-flags which is not currently supported.
-                panic!("Compile-flags is not currently supported.")
+    let Some((compiler, args)) = args.split_first() else {
+        bail!("Arguments section is empty.");
+    };
+
+    let mut kept_args = vec![];
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match conflicting_flag(arg) {
+            // The value of the flag is the next argument.
+            Some((flag, true)) if arg == flag => {
+                args.next();
             }
+            Some(_) => (),
+            None => kept_args.push(arg.clone()),
+        }
+    }
+
+    Ok((compiler.clone(), kept_args))
+}
+
+/// Returns the conflicting flag which the argument is, where the flags which take a value
+/// match both the argument which is followed by the value, such as `-o out.o`, and the
+/// argument which is joined with it, such as `-oout.o`.
+fn conflicting_flag(arg: &str) -> Option<(&'static str, bool)> {
+    CONFLICTING_FLAGS
+        .iter()
+        .find(|(flag, takes_value)| {
+            arg == *flag
+                || (*takes_value
+                    && arg
+                        .strip_prefix(flag)
+                        .is_some_and(|value| !value.is_empty()))
+        })
+        .copied()
+}
+
+/// Preprocesses a single file of an entry of compile-commands.
+fn preprocess_file(
+    compile_command: &CompileCommand,
+    source_file: &SourceFileKind,
+) -> anyhow::Result<SourceInfo> {
+    let Some(directory) = compile_command.directory.to_str() else {
+        bail!("UTF-8 validity for directory section failed.");
+    };
+
+    let (compiler, mut args) = compiler_args(compile_command)?;
+
+    let path = match source_file {
+        SourceFileKind::Listed(path) => path.clone(),
+        SourceFileKind::Found(path) => {
+            args.push(path.clone());
+            path.clone()
+        }
+    };
+
+    let output = Command::new(&compiler)
+        .arg(PREPROCESS_ONLY_FLAG)
+        .arg(KEEP_COMMENTS_FLAG)
+        .arg(INCLUDE_FLAG)
+        .arg(annotated_lib_dir()?)
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .with_context(|| format!("Failed to run '{compiler}'."))?;
+
+    if !output.status.success() {
+        bail!(
+            "'{compiler}' exited with {} while preprocessing '{path}':\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+
+    Ok(SourceInfo::new(
+        path,
+        &compile_command.directory,
+        output.stdout,
+    ))
+}
+
+/// Preprocesses the source code which is given as a string the same way as the entries of
+/// compile-commands, while passing the flags to the compiler and naming the code by the path.
+pub fn preprocess_source(path: &str, code: &str, flags: &[String]) -> anyhow::Result<SourceInfo> {
+    preprocess_stdin(DEFAULT_COMPILER, flags, Path::new("."), path, code)
+}
+
+/// Preprocesses the source code by passing it to the standard input of the compiler, which is
+/// run with the arguments in the directory.
+fn preprocess_stdin(
+    compiler: &str,
+    args: &[String],
+    directory: &Path,
+    path: &str,
+    code: &str,
+) -> anyhow::Result<SourceInfo> {
+    let mut child = Command::new(compiler)
+        .arg(PREPROCESS_ONLY_FLAG)
+        .arg(KEEP_COMMENTS_FLAG)
+        .arg(INCLUDE_FLAG)
+        .arg(annotated_lib_dir()?)
+        .args(args)
+        .args(STDIN_SOURCE_FLAGS)
+        .current_dir(directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run '{compiler}'."))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("Failed to open the standard input of the compiler.")?;
+
+    // The source code is written on another thread, since the compiler may fill its output
+    // before reading all of the code. The standard input is closed once it is written.
+    let (written, output) = thread::scope(|scope| {
+        let writer = scope.spawn(move || stdin.write_all(code.as_bytes()));
+        let output = child.wait_with_output();
+
+        (writer.join(), output)
+    });
+
+    written
+        .map_err(|_| anyhow!("Failed to pass the source code to the compiler."))?
+        .context("Failed to pass the source code to the compiler.")?;
+
+    let output = output.with_context(|| format!("Failed to run '{compiler}'."))?;
+
+    if !output.status.success() {
+        bail!(
+            "'{compiler}' exited with {} while preprocessing '{path}':\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+
+    Ok(SourceInfo::new(path.to_owned(), directory, output.stdout))
+}
+
+/// Returns the directory which the annotated headers are deployed to.
+pub fn annotated_lib_dir() -> anyhow::Result<PathBuf> {
+    let dirs = directories::ProjectDirs::from("ir", "skye-tan", "azhdaha")
+        .context("Failed to find the data directory of azhdaha.")?;
+
+    Ok(dirs.data_dir().join(ANNOTATED_LIB_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use azhdaha_errors::{Source, Span};
+    use compile_commands::{CompileArgs, CompileCommand, SourceFile};
+
+    use super::{SourceInfo, compiler_args};
+
+    fn compile_command(arguments: Option<&[&str]>, command: Option<&str>) -> CompileCommand {
+        CompileCommand {
+            directory: PathBuf::from("/project"),
+            file: SourceFile::File(PathBuf::from("main.c")),
+            arguments: arguments.map(|arguments| {
+                CompileArgs::Arguments(arguments.iter().map(|arg| (*arg).to_owned()).collect())
+            }),
+            command: command.map(str::to_owned),
+            output: None,
+        }
+    }
+
+    #[test]
+    fn splits_the_quoted_arguments_of_the_command() {
+        let compile_command = compile_command(
+            None,
+            Some(r#"gcc -DNAME="a b" '-I/path with spaces' -c main.c"#),
+        );
+
+        let (compiler, args) = compiler_args(&compile_command).unwrap();
+
+        assert_eq!(compiler, "gcc");
+        assert_eq!(args, ["-DNAME=a b", "-I/path with spaces", "main.c"]);
+    }
+
+    #[test]
+    fn strips_the_output_flag_along_with_its_value() {
+        let separate = compile_command(Some(&["gcc", "-o", "out.o", "-O2", "main.c"]), None);
+        let joined = compile_command(Some(&["gcc", "-oout.o", "-O2", "main.c"]), None);
+
+        assert_eq!(compiler_args(&separate).unwrap().1, ["-O2", "main.c"]);
+        assert_eq!(compiler_args(&joined).unwrap().1, ["-O2", "main.c"]);
+    }
+
+    #[test]
+    fn strips_the_dependency_flags() {
+        let compile_command = compile_command(
+            Some(&[
+                "gcc",
+                "-MD",
+                "-MF",
+                "main.d",
+                "-MTmain.o",
+                "-MMD",
+                "-c",
+                "main.c",
+            ]),
+            None,
+        );
+
+        assert_eq!(compiler_args(&compile_command).unwrap().1, ["main.c"]);
+    }
+
+    #[test]
+    fn keeps_the_flags_which_only_share_a_prefix_with_the_conflicting_flags() {
+        let compile_command = compile_command(
+            Some(&["gcc", "-Map", "-MDx", "-Cc", "-Sx", "-pthread", "main.c"]),
+            None,
+        );
+
+        assert_eq!(
+            compiler_args(&compile_command).unwrap().1,
+            ["-Map", "-MDx", "-Cc", "-Sx", "-pthread", "main.c"]
+        );
+    }
+
+    #[test]
+    fn names_the_default_compiler_for_the_flags() {
+        let compile_command = CompileCommand {
+            arguments: Some(CompileArgs::Flags(vec![
+                "-c".to_owned(),
+                "-Wall".to_owned(),
+            ])),
+            ..compile_command(None, None)
         };
 
-        let dirs = directories::ProjectDirs::from("ir", "skye-tan", "azhdaha").unwrap();
+        let (compiler, args) = compiler_args(&compile_command).unwrap();
 
-        results.push(SourceInfo {
-            path,
-            code: Command::new(command)
-                .arg(PREPROCESS_ONLY_FLAG)
-                .arg(INHABIT_LINEMARKS_FLAG)
-                .arg(INCLUDE_FLAG)
-                .arg(dirs.data_dir().join(ANNOTATED_LIB_PATH))
-                .args(args)
-                .current_dir(directory)
-                .output()?
-                .stdout,
-        });
-    }
-
-    Ok(results)
+        assert_eq!(compiler, super::DEFAULT_COMPILER);
+        assert_eq!(args, ["-Wall"]);
+    }
+
+    #[test]
+    fn clamps_the_locations_of_the_expanded_macros_to_the_original_lines() {
+        let directory = env::temp_dir().join(format!("azhdaha-preprocess-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("main.c"),
+            "#define ALLOCATE(size) malloc(size * sizeof(long long int))\nint *p = ALLOCATE(4);\n",
+        )
+        .unwrap();
+
+        let path = directory.join("main.c").to_string_lossy().into_owned();
+        let code = b"# 1 \"main.c\"\n\nint *p = malloc(4 * sizeof(long long int));\n".to_vec();
+        let source_info = SourceInfo::new(path.clone(), &directory, code);
+
+        // The call which the macro expands to ends past the end of the original line.
+        let location = source_info.location(Span { lo: 23, hi: 56 });
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(location.file, path);
+        assert_eq!((location.line, location.column), (2, 10));
+        assert_eq!((location.end_line, location.end_column), (2, 22));
+        assert_eq!((location.lo, location.hi), (Some(69), Some(81)));
+    }
 }
@@ -0,0 +1,415 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{fs, path::Path, sync::OnceLock};
+
+/// A position in one of the files which the preprocessed source code is expanded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition<'map> {
+    pub file: &'map str,
+    /// The line in the file, starting from one.
+    pub line: usize,
+    /// The column in the line in characters, starting from one.
+    pub column: usize,
+}
+
+/// A linemarker which indicates that the following lines of the preprocessed source code
+/// are expanded from a line of a file onward.
+#[derive(Debug, Clone, Copy)]
+struct LineMarker {
+    /// The line of the preprocessed source code which follows the linemarker, starting from
+    /// zero.
+    preprocessed_line: usize,
+    file: usize,
+    /// The line in the file which the following line is expanded from, starting from one.
+    line: usize,
+}
+
+/// Maps the lines of the preprocessed source code back to the lines of the original files
+/// by the linemarkers which the preprocessor emits.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<String>,
+    /// The name which the linemarkers give to the main file, which is relative to the
+    /// directory which the preprocessor was run in.
+    main_file: Option<String>,
+    markers: Vec<LineMarker>,
+    /// The offset of the beginning of each line of the preprocessed source code.
+    line_starts: Vec<usize>,
+    /// The byte ranges of the linemarkers in the preprocessed source code.
+    pub(crate) marker_ranges: Vec<(usize, usize)>,
+    /// The contents of the files, which are read the first time that an offset in them is
+    /// asked for, or `None` when they cannot be read.
+    contents: Vec<OnceLock<Option<Vec<u8>>>>,
+}
+
+impl SourceMap {
+    /// Collects the linemarkers of the preprocessed source code. The files named in the
+    /// linemarkers are resolved relative to the directory which the preprocessor was run
+    /// in, except for the main file which is named by the given path.
+    pub(crate) fn new(path: &str, directory: &Path, code: &[u8]) -> Self {
+        let mut source_map = Self {
+            files: vec![],
+            main_file: None,
+            markers: vec![],
+            line_starts: vec![0],
+            marker_ranges: vec![],
+            contents: vec![],
+        };
+
+        let mut line_start = 0;
+
+        for (preprocessed_line, line) in code.split(|byte| *byte == b'\n').enumerate() {
+            let line_end = line_start + line.len();
+
+            if line_end < code.len() {
+                source_map.line_starts.push(line_end + 1);
+            }
+
+            if let Some((line, file)) = parse_linemarker(line) {
+                let file = match source_map.main_file {
+                    None => {
+                        source_map.main_file = Some(file.to_owned());
+
+                        path.to_owned()
+                    }
+                    Some(ref main_file) if main_file == file => path.to_owned(),
+                    // The names such as `<built-in>` do not refer to files.
+                    Some(_) if file.starts_with('<') => file.to_owned(),
+                    Some(_) => directory.join(file).to_string_lossy().into_owned(),
+                };
+
+                let file = source_map.file_index(file);
+
+                source_map.markers.push(LineMarker {
+                    preprocessed_line: preprocessed_line + 1,
+                    file,
+                    line,
+                });
+                source_map.marker_ranges.push((line_start, line_end));
+            }
+
+            line_start = line_end + 1;
+        }
+
+        source_map
+    }
+
+    fn file_index(&mut self, file: String) -> usize {
+        match self.files.iter().position(|other| *other == file) {
+            Some(index) => index,
+            None => {
+                self.files.push(file);
+                self.contents.push(OnceLock::new());
+                self.files.len() - 1
+            }
+        }
+    }
+
+    /// Returns the names of the files which the linemarkers refer to, including the names
+    /// such as `<built-in>` which do not refer to files. The main file is named the way the
+    /// linemarkers name it rather than by the given path.
+    pub(crate) fn files<'map>(&'map self, path: &'map str) -> impl Iterator<Item = &'map str> {
+        self.files
+            .iter()
+            .map(move |file| match self.main_file.as_deref() {
+                Some(main_file) if file == path => main_file,
+                _ => file,
+            })
+    }
+
+    /// Returns the position in the original file which the offset of the preprocessed source
+    /// code is expanded from, or the position in the preprocessed source code itself when no
+    /// linemarker precedes it.
+    pub(crate) fn resolve<'map>(
+        &'map self,
+        path: &'map str,
+        code: &[u8],
+        offset: usize,
+    ) -> SourcePosition<'map> {
+        let offset = offset.min(code.len());
+
+        let preprocessed_line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= offset)
+            - 1;
+        let line_start = self.line_starts[preprocessed_line];
+
+        let column = String::from_utf8_lossy(&code[line_start..offset])
+            .chars()
+            .count()
+            + 1;
+
+        let marker_index = self
+            .markers
+            .partition_point(|marker| marker.preprocessed_line <= preprocessed_line);
+
+        match marker_index.checked_sub(1).map(|index| self.markers[index]) {
+            Some(marker) => SourcePosition {
+                file: &self.files[marker.file],
+                line: marker.line + preprocessed_line - marker.preprocessed_line,
+                column,
+            },
+            None => SourcePosition {
+                file: path,
+                line: preprocessed_line + 1,
+                column,
+            },
+        }
+    }
+
+    /// Returns the offset of the preprocessed source code which the position in the original
+    /// file is expanded to, where the last expansion is chosen when the file is included more
+    /// than once.
+    pub(crate) fn offset_of(
+        &self,
+        path: &str,
+        code: &[u8],
+        position: SourcePosition<'_>,
+    ) -> Option<usize> {
+        let mut offset = None;
+
+        for (index, marker) in self.markers.iter().enumerate() {
+            if self.files[marker.file] != position.file || position.line < marker.line {
+                continue;
+            }
+
+            // The lines of the file continue until the next linemarker.
+            let end_line = self
+                .markers
+                .get(index + 1)
+                .map_or(self.line_starts.len(), |next| next.preprocessed_line - 1);
+
+            let preprocessed_line = marker.preprocessed_line + position.line - marker.line;
+
+            if preprocessed_line < end_line {
+                offset = Some(self.column_offset(code, preprocessed_line, position.column));
+            }
+        }
+
+        // The source code which is not preprocessed has no linemarkers.
+        if self.markers.is_empty() && position.file == path {
+            let preprocessed_line = position.line.checked_sub(1)?;
+
+            if preprocessed_line < self.line_starts.len() {
+                offset = Some(self.column_offset(code, preprocessed_line, position.column));
+            }
+        }
+
+        offset
+    }
+
+    /// Returns the offset in bytes of the position in its original file, which is clamped to
+    /// the end of its line, or `None` when the file cannot be read. The files are resolved
+    /// relative to the directory which the preprocessor was run in.
+    pub(crate) fn file_offset(
+        &self,
+        path: &str,
+        directory: &Path,
+        position: SourcePosition<'_>,
+    ) -> Option<usize> {
+        let (line_start, line) = self.original_line(path, directory, position)?;
+
+        Some(
+            String::from_utf8_lossy(line)
+                .char_indices()
+                .nth(position.column.saturating_sub(1))
+                .map_or(line_start + line.len(), |(offset, _)| line_start + offset),
+        )
+    }
+
+    /// Returns the column of the position which is clamped to the column right after the end
+    /// of its line in its original file, since the columns are counted in the preprocessed
+    /// source code where the macros of the line are expanded. The column stays the same when
+    /// the file cannot be read.
+    pub(crate) fn file_column(
+        &self,
+        path: &str,
+        directory: &Path,
+        position: SourcePosition<'_>,
+    ) -> usize {
+        match self.original_line(path, directory, position) {
+            Some((_, line)) => position
+                .column
+                .min(String::from_utf8_lossy(line).chars().count() + 1),
+            None => position.column,
+        }
+    }
+
+    /// Returns the offset of the beginning of the line of the position in its original file
+    /// along with the line without its line break.
+    fn original_line(
+        &self,
+        path: &str,
+        directory: &Path,
+        position: SourcePosition<'_>,
+    ) -> Option<(usize, &[u8])> {
+        let index = self.files.iter().position(|file| file == position.file)?;
+
+        let contents = self.contents[index]
+            .get_or_init(|| {
+                let file = self.files(path).nth(index)?;
+
+                fs::read(directory.join(file)).ok()
+            })
+            .as_deref()?;
+
+        let line_start: usize = contents
+            .split_inclusive(|byte| *byte == b'\n')
+            .take(position.line.checked_sub(1)?)
+            .map(<[u8]>::len)
+            .sum();
+        let line_end = contents[line_start..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(contents.len(), |length| line_start + length);
+
+        Some((line_start, &contents[line_start..line_end]))
+    }
+
+    /// Returns the offset of the column in the line of the preprocessed source code, which is
+    /// clamped to the end of the line.
+    fn column_offset(&self, code: &[u8], preprocessed_line: usize, column: usize) -> usize {
+        let line_start = self.line_starts[preprocessed_line];
+        let line_end = self.line_end(code, line_start);
+
+        String::from_utf8_lossy(&code[line_start..line_end])
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(line_end, |(offset, _)| line_start + offset)
+    }
+
+    /// Returns the offset of the end of the line of the preprocessed source code which the
+    /// offset is in.
+    pub(crate) fn line_end(&self, code: &[u8], offset: usize) -> usize {
+        let next_line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= offset);
+
+        self.line_starts
+            .get(next_line)
+            .map_or(code.len(), |line_start| line_start - 1)
+    }
+}
+
+/// Parses a linemarker such as `# 12 "file.c" 2` into the line and the name of the file.
+fn parse_linemarker(line: &[u8]) -> Option<(usize, &str)> {
+    let line = str::from_utf8(line).ok()?.strip_prefix("# ")?;
+
+    let (number, rest) = line.split_once(' ')?;
+    let file = rest.strip_prefix('"')?;
+    let file = &file[..file.rfind('"')?];
+
+    Some((number.parse().ok()?, file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path, process};
+
+    use super::{SourceMap, SourcePosition, parse_linemarker};
+
+    const CODE: &[u8] = b"# 1 \"src/main.c\"\n# 1 \"<built-in>\"\n# 1 \"src/main.c\"\n# 1 \"include/util.h\" 1\nint util;\n# 3 \"src/main.c\" 2\n\nint main;\n";
+
+    fn position(file: &str, line: usize, column: usize) -> SourcePosition<'_> {
+        SourcePosition { file, line, column }
+    }
+
+    #[test]
+    fn parses_the_linemarkers() {
+        assert_eq!(parse_linemarker(b"# 12 \"file.c\" 2"), Some((12, "file.c")));
+        assert_eq!(
+            parse_linemarker(b"# 1 \"dir \\\"name\\\"/file.c\""),
+            Some((1, "dir \\\"name\\\"/file.c"))
+        );
+        assert_eq!(parse_linemarker(b"#define X 1"), None);
+        assert_eq!(parse_linemarker(b"# x \"file.c\""), None);
+    }
+
+    #[test]
+    fn names_the_files_of_the_linemarkers() {
+        let source_map = SourceMap::new("/project/src/main.c", Path::new("/project"), CODE);
+
+        assert_eq!(
+            source_map.files("/project/src/main.c").collect::<Vec<_>>(),
+            ["src/main.c", "<built-in>", "/project/include/util.h"]
+        );
+        assert_eq!(source_map.marker_ranges.len(), 5);
+    }
+
+    #[test]
+    fn resolves_the_offsets_to_the_original_files() {
+        let path = "/project/src/main.c";
+        let source_map = SourceMap::new(path, Path::new("/project"), CODE);
+
+        let util = CODE
+            .windows(5)
+            .position(|window| window == b"util;")
+            .unwrap();
+        let main = CODE
+            .windows(5)
+            .position(|window| window == b"main;")
+            .unwrap();
+
+        assert_eq!(
+            source_map.resolve(path, CODE, util),
+            position("/project/include/util.h", 1, 5)
+        );
+        assert_eq!(source_map.resolve(path, CODE, main), position(path, 4, 5));
+
+        assert_eq!(
+            source_map.offset_of(path, CODE, position("/project/include/util.h", 1, 5)),
+            Some(util)
+        );
+        assert_eq!(
+            source_map.offset_of(path, CODE, position(path, 4, 5)),
+            Some(main)
+        );
+    }
+
+    #[test]
+    fn resolves_the_offsets_without_linemarkers() {
+        let code = b"int a;\nint b;\n";
+        let source_map = SourceMap::new("main.c", Path::new("."), code);
+
+        assert_eq!(
+            source_map.resolve("main.c", code, 11),
+            position("main.c", 2, 5)
+        );
+        assert_eq!(
+            source_map.offset_of("main.c", code, position("main.c", 2, 5)),
+            Some(11)
+        );
+        // The columns past the end of the line are clamped to it.
+        assert_eq!(
+            source_map.offset_of("main.c", code, position("main.c", 1, 40)),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn finds_the_offsets_in_the_original_files() {
+        let directory = env::temp_dir().join(format!("azhdaha-source-map-{}", process::id()));
+        fs::create_dir_all(directory.join("src")).unwrap();
+        fs::write(directory.join("src/main.c"), "// é\nint main;\n").unwrap();
+
+        let path = directory.join("src/main.c").to_string_lossy().into_owned();
+        let code = b"# 1 \"src/main.c\"\n// \xc3\xa9\nint main;\n";
+        let source_map = SourceMap::new(&path, &directory, code);
+
+        assert_eq!(
+            source_map.file_offset(&path, &directory, position(&path, 2, 5)),
+            Some(10)
+        );
+        // The columns are counted in characters and clamped to the end of the line.
+        assert_eq!(
+            source_map.file_offset(&path, &directory, position(&path, 1, 5)),
+            Some(5)
+        );
+        assert_eq!(
+            source_map.file_offset(&path, &directory, position("other.c", 1, 1)),
+            None
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Span};
+
+/// Identifies a source file by its path.
+pub type FileId = String;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// The name of the severity which is used by the rendered reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+/// The stage of the tool which produced a diagnostic. The diagnostics of the lowering stages
+/// and of the functions which failed to be analyzed are failures of the tool rather than
+/// findings in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Hir,
+    Mir,
+    /// The analysis of a function which failed before its states reached a fixpoint.
+    Dataflow,
+    Analysis,
+}
+
+impl Stage {
+    /// Whether the diagnostic means that the tool failed to process an item.
+    pub fn is_failure(self) -> bool {
+        matches!(self, Stage::Hir | Stage::Mir | Stage::Dataflow)
+    }
+}
+
+/// A range in one of the original source files, which the preprocessed source code is
+/// expanded from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub file: FileId,
+    /// The line of the start of the range, starting from one.
+    pub line: usize,
+    /// The column of the start of the range in characters, starting from one.
+    pub column: usize,
+    /// The line of the end of the range, starting from one.
+    pub end_line: usize,
+    /// The column right after the end of the range in characters, starting from one.
+    pub end_column: usize,
+    /// The offset in bytes of the start of the range in the file, which is `None` when the
+    /// file cannot be read.
+    pub lo: Option<usize>,
+    /// The offset in bytes right after the end of the range in the file.
+    pub hi: Option<usize>,
+    /// The range in the preprocessed source code.
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A message which is attached to a secondary location of a diagnostic, such as the place
+/// which a variable is defined or moved at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Label {
+    pub message: String,
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+/// A problem which is found in a source file, either by the analysis or while lowering it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub stage: Stage,
+    /// The code which identifies the kind of the diagnostic, which stays the same between
+    /// releases.
+    pub code: Option<&'static str>,
+    /// The name of the kind of the diagnostic, such as `memory_leakage`, which is kept along
+    /// with the code for the consumers which identify the diagnostics by their names.
+    pub kind: Option<&'static str>,
+    pub message: String,
+    #[serde(flatten)]
+    pub location: Location,
+    /// The name of the function which the diagnostic is found in.
+    pub function: Option<String>,
+    /// The name of the variable which the diagnostic is about.
+    pub variable: Option<String>,
+    /// The first line of the reported code.
+    pub snippet: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub helps: Vec<String>,
+    /// The line which the function containing the diagnostic is defined at.
+    #[serde(skip)]
+    pub function_line: Option<usize>,
+}
+
+/// The preprocessed source code of a translation unit, which the spans of its diagnostics
+/// point into.
+pub trait Source {
+    fn path(&self) -> &str;
+    fn code(&self) -> &[u8];
+    /// Maps the span of the preprocessed source code to the original files.
+    fn location(&self, span: Span) -> Location;
+    /// Returns the first line of the code in the span.
+    fn snippet(&self, span: Span) -> String;
+}
+
+impl Error {
+    /// Converts the error into a diagnostic of the stage which failed to process an item.
+    pub fn to_diagnostic(&self, stage: Stage, source: &dyn Source) -> Diagnostic {
+        let (message, span) = &self.primary_message;
+
+        Diagnostic {
+            severity: Severity::Error,
+            stage,
+            code: None,
+            kind: None,
+            message: message.clone(),
+            location: source.location(*span),
+            function: None,
+            variable: None,
+            snippet: source.snippet(*span),
+            labels: self
+                .additional_messages
+                .iter()
+                .map(|(message, span)| Label {
+                    message: message.clone(),
+                    location: source.location(*span),
+                })
+                .collect(),
+            notes: vec!["The item is left out of the analysis".to_owned()],
+            helps: vec![],
+            function_line: None,
+        }
+    }
+}
@@ -1,10 +1,15 @@
-//! Facilities for rendering errors beatifully.
+//! The diagnostics which are reported by the frontend and the analyzer, along with the
+//! facilities for rendering them beatifully.
 
-use std::ops::Range;
+/// Contains the model of the diagnostics which is shared by all of the stages.
+mod diagnostic;
+/// Contains the formats which the diagnostics are rendered in.
+mod render;
 
-use annotate_snippets::{AnnotationKind, Level, Renderer, Snippet, renderer::DecorStyle};
+pub use diagnostic::{Diagnostic, FileId, Label, Location, Severity, Source, Stage};
+pub use render::{HumanRenderer, JsonRenderer, Render, Rule, SarifRenderer};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Span {
     pub lo: usize,
     pub hi: usize,
@@ -12,11 +17,6 @@ pub struct Span {
 
 impl Span {
     pub const DUMMY: Self = Self { lo: 0, hi: 0 };
-
-    /// Convert span to a range.
-    fn to_range(self) -> Range<usize> {
-        self.lo..self.hi
-    }
 }
 
 #[derive(Debug)]
@@ -25,30 +25,6 @@ pub struct Error {
     pub additional_messages: Vec<(String, Span)>,
 }
 
-impl Error {
-    #[allow(clippy::print_stderr)]
-    pub fn report(&self, source: &str) {
-        let mut section = Snippet::source(source).annotation(
-            AnnotationKind::Primary
-                .span(self.primary_message.1.to_range())
-                .label(&self.primary_message.0),
-        );
-
-        for (message, span) in &self.additional_messages {
-            section =
-                section.annotation(AnnotationKind::Context.span(span.to_range()).label(message));
-        }
-        let element = Level::ERROR
-            .primary_title(&self.primary_message.0)
-            .element(section);
-
-        let report = &[element];
-
-        let renderer = Renderer::styled().decor_style(DecorStyle::Unicode);
-        eprintln!("{}", renderer.render(report));
-    }
-}
-
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub trait Context: Sized {
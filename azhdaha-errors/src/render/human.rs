@@ -0,0 +1,215 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    ops::Range,
+};
+
+use annotate_snippets::{
+    Annotation, AnnotationKind, Group, Level, Renderer, Snippet, renderer::DecorStyle,
+};
+use anstyle::{Color, RgbColor, Style};
+
+use crate::{
+    diagnostic::{Diagnostic, FileId, Location, Severity, Source, Stage},
+    render::Render,
+};
+
+/// The style which the names quoted by backticks in the messages are highlighted with.
+const NAME_STYLE: Style = Style::new().fg_color(Some(Color::Rgb(RgbColor(255, 165, 0))));
+
+/// Renders the diagnostics as reports which quote the original files, or the preprocessed
+/// source code when the files cannot be read.
+pub struct HumanRenderer<W> {
+    writer: W,
+    renderer: Renderer,
+    /// The contents of the original files, which are `None` when they cannot be read.
+    files: HashMap<FileId, Option<String>>,
+}
+
+/// A range which is annotated in one of the quoted files.
+struct Marker<'a> {
+    file: FileId,
+    range: Range<usize>,
+    kind: AnnotationKind,
+    label: Option<&'a str>,
+}
+
+impl<W: Write> HumanRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            renderer: Renderer::styled().decor_style(DecorStyle::Unicode),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Returns the file which quotes the location along with the range of the location in it.
+    fn quote(&mut self, location: &Location, source: &dyn Source) -> (FileId, Range<usize>) {
+        let file = self
+            .files
+            .entry(location.file.clone())
+            .or_insert_with(|| fs::read_to_string(&location.file).ok());
+
+        if let Some(file) = file
+            && let (Some(lo), Some(hi)) = (
+                offset_of(file, location.line, location.column),
+                offset_of(file, location.end_line, location.end_column),
+            )
+        {
+            return (location.file.clone(), lo..hi.max(lo));
+        }
+
+        let preprocessed_id = preprocessed_id(source);
+
+        self.files
+            .entry(preprocessed_id.clone())
+            .or_insert_with(|| Some(String::from_utf8_lossy(source.code()).into_owned()));
+
+        (
+            preprocessed_id,
+            location.span.lo..location.span.hi.max(location.span.lo),
+        )
+    }
+}
+
+impl<W: Write> Render for HumanRenderer<W> {
+    fn render(&mut self, diagnostic: &Diagnostic, source: &dyn Source) -> io::Result<()> {
+        // The label at the location of the diagnostic is the primary one, or the last label
+        // when there is no such label, since it is the closest one to the problem.
+        let primary_index = diagnostic
+            .labels
+            .iter()
+            .position(|label| label.location == diagnostic.location)
+            .or_else(|| diagnostic.labels.len().checked_sub(1));
+
+        let mut markers = vec![];
+
+        if primary_index.is_none() {
+            let (file, range) = self.quote(&diagnostic.location, source);
+
+            markers.push(Marker {
+                file,
+                range,
+                kind: AnnotationKind::Primary,
+                label: None,
+            });
+        }
+
+        for (index, label) in diagnostic.labels.iter().enumerate() {
+            let (file, range) = self.quote(&label.location, source);
+
+            markers.push(Marker {
+                file,
+                range,
+                kind: if Some(index) == primary_index {
+                    AnnotationKind::Primary
+                } else {
+                    AnnotationKind::Context
+                },
+                label: Some(&label.message),
+            });
+        }
+
+        // The file of the primary marker is quoted first, so the report is headed with it.
+        markers.sort_by_key(|marker| !matches!(marker.kind, AnnotationKind::Primary));
+
+        let mut files: Vec<&FileId> = vec![];
+
+        for marker in &markers {
+            if !files.contains(&&marker.file) {
+                files.push(&marker.file);
+            }
+        }
+
+        let level = match (diagnostic.stage, diagnostic.severity) {
+            (Stage::Hir, _) => Level::ERROR.with_name("HIR lowering error"),
+            (Stage::Mir, _) => Level::ERROR.with_name("MIR lowering error"),
+            (Stage::Dataflow, _) => Level::ERROR.with_name("Analysis error"),
+            (Stage::Analysis, Severity::Error) => Level::ERROR,
+            (Stage::Analysis, Severity::Warning) => Level::WARNING,
+            (Stage::Analysis, Severity::Note) => Level::NOTE,
+        };
+
+        let mut title = level.primary_title(highlight(&diagnostic.message));
+
+        if let Some(code) = diagnostic.code {
+            title = title.id(code);
+        }
+
+        let snippets = files.iter().map(|file| {
+            let annotations: Vec<Annotation> = markers
+                .iter()
+                .filter(|marker| marker.file == **file)
+                .map(|marker| {
+                    marker
+                        .kind
+                        .span(marker.range.clone())
+                        .label(marker.label.map(highlight))
+                })
+                .collect();
+
+            Snippet::source(self.files[*file].as_deref().unwrap_or_default())
+                .path(file.as_str())
+                .annotations(annotations)
+        });
+
+        let group = Group::with_title(title)
+            .elements(snippets)
+            .elements(
+                diagnostic
+                    .notes
+                    .iter()
+                    .map(|note| Level::NOTE.message(highlight(note))),
+            )
+            .elements(
+                diagnostic
+                    .helps
+                    .iter()
+                    .map(|help| Level::HELP.message(highlight(help))),
+            );
+
+        writeln!(self.writer, "{}", self.renderer.render(&[group]))
+    }
+}
+
+/// The name which the preprocessed source code is quoted by.
+fn preprocessed_id(source: &dyn Source) -> FileId {
+    format!("{} (preprocessed)", source.path())
+}
+
+/// Returns the byte offset of the position in the code, whose line and column start from one.
+fn offset_of(code: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line == 1 {
+        0
+    } else {
+        code.match_indices('\n').nth(line.checked_sub(2)?)?.0 + 1
+    };
+
+    let line = &code[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+
+    let column_offset = line
+        .char_indices()
+        .nth(column.checked_sub(1)?)
+        .map_or(line.len(), |(offset, _)| offset);
+
+    Some(line_start + column_offset)
+}
+
+/// Highlights the names which are quoted by backticks in the message.
+fn highlight(message: &str) -> String {
+    message
+        .split('`')
+        .enumerate()
+        .map(|(index, part)| {
+            if index % 2 == 1 {
+                format!("{NAME_STYLE}`{part}`{NAME_STYLE:#}")
+            } else {
+                part.to_owned()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,108 @@
+use std::io::{self, Write};
+
+use crate::{
+    diagnostic::{Diagnostic, Source},
+    render::Render,
+};
+
+/// Writes each diagnostic as a JSON record on its own line.
+pub struct JsonRenderer<W> {
+    /// The output which the records are written to.
+    writer: W,
+}
+
+impl<W: Write> JsonRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Render for JsonRenderer<W> {
+    fn render(&mut self, diagnostic: &Diagnostic, _source: &dyn Source) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, diagnostic)?;
+        writeln!(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::JsonRenderer;
+    use crate::{
+        Span,
+        diagnostic::{Diagnostic, Location, Severity, Source, Stage},
+        render::Render,
+    };
+
+    struct TestSource;
+
+    impl Source for TestSource {
+        fn path(&self) -> &str {
+            "main.c"
+        }
+
+        fn code(&self) -> &[u8] {
+            b"int main;\n"
+        }
+
+        fn location(&self, span: Span) -> Location {
+            Location {
+                file: "main.c".to_owned(),
+                line: 1,
+                column: span.lo + 1,
+                end_line: 1,
+                end_column: span.hi + 1,
+                lo: Some(span.lo),
+                hi: Some(span.hi),
+                span,
+            }
+        }
+
+        fn snippet(&self, _span: Span) -> String {
+            "int main;".to_owned()
+        }
+    }
+
+    #[test]
+    fn writes_the_code_and_the_kind_of_each_diagnostic() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            stage: Stage::Analysis,
+            code: Some("AZ0001"),
+            kind: Some("memory_leakage"),
+            message: "The memory is leaked.".to_owned(),
+            location: TestSource.location(Span { lo: 4, hi: 8 }),
+            function: None,
+            variable: None,
+            snippet: TestSource.snippet(Span { lo: 4, hi: 8 }),
+            labels: vec![],
+            notes: vec![],
+            helps: vec![],
+            function_line: None,
+        };
+
+        let mut output = vec![];
+        let mut renderer = JsonRenderer::new(&mut output);
+        renderer.render(&diagnostic, &TestSource).unwrap();
+        renderer.render(&diagnostic, &TestSource).unwrap();
+        renderer.finish().unwrap();
+
+        let records: Vec<Value> = output
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["code"], "AZ0001");
+        assert_eq!(records[0]["kind"], "memory_leakage");
+        assert_eq!(records[0]["severity"], "error");
+        assert_eq!(records[0]["stage"], "analysis");
+        assert_eq!(records[0]["file"], "main.c");
+        assert_eq!(records[0]["column"], 5);
+        assert_eq!(records[0]["lo"], 4);
+        assert_eq!(records[0].get("span"), None);
+        assert_eq!(records[0]["snippet"], json!("int main;"));
+    }
+}
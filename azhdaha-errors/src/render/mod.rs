@@ -0,0 +1,35 @@
+use std::io;
+
+use crate::diagnostic::{Diagnostic, Severity, Source};
+
+/// Contains the human-readable reports which quote the original files.
+mod human;
+/// Contains the JSON records which are written one per line.
+mod json;
+/// Contains the SARIF log which is consumed by code scanning services.
+mod sarif;
+
+pub use human::HumanRenderer;
+pub use json::JsonRenderer;
+pub use sarif::SarifRenderer;
+
+/// A kind of diagnostic which is described by the SARIF log.
+#[derive(Debug, Clone, Copy)]
+pub struct Rule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub severity: Severity,
+}
+
+/// A format which the diagnostics are written in.
+pub trait Render {
+    /// Writes the diagnostic which is found in the source, or keeps it until the output is
+    /// finished.
+    fn render(&mut self, diagnostic: &Diagnostic, source: &dyn Source) -> io::Result<()>;
+
+    /// Writes the rest of the output after all of the diagnostics are rendered.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::io::{self, Write};
+
+use serde_json::{Value, json};
+
+use crate::{
+    diagnostic::{Diagnostic, Location, Source},
+    render::{Render, Rule},
+};
+
+/// The URI of the JSON schema of SARIF 2.1.0.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders a SARIF log which has a single run containing all of the diagnostics. The
+/// diagnostics of the items which failed to be lowered or analyzed are failures of the tool
+/// rather than results, so they are reported as the notifications of the invocation.
+pub struct SarifRenderer<W> {
+    writer: W,
+    tool_name: &'static str,
+    tool_version: &'static str,
+    rules: Vec<Rule>,
+    results: Vec<Value>,
+    notifications: Vec<Value>,
+}
+
+impl<W: Write> SarifRenderer<W> {
+    pub fn new(
+        writer: W,
+        tool_name: &'static str,
+        tool_version: &'static str,
+        rules: Vec<Rule>,
+    ) -> Self {
+        Self {
+            writer,
+            tool_name,
+            tool_version,
+            rules,
+            results: vec![],
+            notifications: vec![],
+        }
+    }
+
+    fn to_result(&self, diagnostic: &Diagnostic) -> Value {
+        let rule_index = self
+            .rules
+            .iter()
+            .position(|rule| Some(rule.id) == diagnostic.code);
+
+        let related_locations: Vec<_> = diagnostic
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                json!({
+                    "id": index,
+                    "physicalLocation": physical_location(&label.location),
+                    "message": { "text": label.message },
+                })
+            })
+            .collect();
+
+        // The labels are collected in the order of the statements on the path which leads to
+        // the violation, from the definition of the variable to the violating statement.
+        let thread_flow_locations: Vec<_> = diagnostic
+            .labels
+            .iter()
+            .map(|label| {
+                json!({
+                    "location": {
+                        "physicalLocation": physical_location(&label.location),
+                        "message": { "text": label.message },
+                    },
+                })
+            })
+            .collect();
+
+        let mut result = json!({
+            "ruleId": diagnostic.code,
+            "ruleIndex": rule_index,
+            "level": diagnostic.severity.name(),
+            "message": { "text": message(diagnostic) },
+            "locations": [{
+                "physicalLocation": physical_location(&diagnostic.location),
+            }],
+            "relatedLocations": related_locations,
+        });
+
+        if !thread_flow_locations.is_empty() {
+            result["codeFlows"] = json!([{
+                "threadFlows": [{ "locations": thread_flow_locations }],
+            }]);
+        }
+
+        result
+    }
+}
+
+impl<W: Write> Render for SarifRenderer<W> {
+    fn render(&mut self, diagnostic: &Diagnostic, _source: &dyn Source) -> io::Result<()> {
+        if diagnostic.stage.is_failure() {
+            self.notifications.push(to_notification(diagnostic));
+        } else {
+            let result = self.to_result(diagnostic);
+            self.results.push(result);
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let rules: Vec<_> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id,
+                    "name": rule.name,
+                    "shortDescription": { "text": rule.description },
+                    "defaultConfiguration": { "level": rule.severity.name() },
+                })
+            })
+            .collect();
+
+        let log = json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": self.tool_name,
+                        "version": self.tool_version,
+                        "rules": rules,
+                    },
+                },
+                "invocations": [{
+                    "executionSuccessful": self.notifications.is_empty(),
+                    "toolExecutionNotifications": self.notifications,
+                }],
+                "results": self.results,
+            }],
+        });
+
+        serde_json::to_writer_pretty(&mut self.writer, &log)?;
+        writeln!(self.writer)
+    }
+}
+
+fn to_notification(diagnostic: &Diagnostic) -> Value {
+    let mut locations = vec![json!({
+        "physicalLocation": physical_location(&diagnostic.location),
+    })];
+
+    locations.extend(diagnostic.labels.iter().map(|label| {
+        json!({
+            "physicalLocation": physical_location(&label.location),
+            "message": { "text": label.message },
+        })
+    }));
+
+    json!({
+        "level": diagnostic.severity.name(),
+        "message": { "text": message(diagnostic) },
+        "locations": locations,
+        "properties": { "stage": diagnostic.stage },
+    })
+}
+
+/// Returns the message of the diagnostic followed by its helps and notes, since SARIF has
+/// no place for them.
+fn message(diagnostic: &Diagnostic) -> String {
+    let mut message = diagnostic.message.clone();
+
+    for help in &diagnostic.helps {
+        message.push_str(&format!("\nHelp: {help}"));
+    }
+
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nNote: {note}"));
+    }
+
+    message
+}
+
+fn physical_location(location: &Location) -> Value {
+    let file = &location.file;
+
+    json!({
+        "artifactLocation": { "uri": file.strip_prefix("./").unwrap_or(file) },
+        "region": {
+            "startLine": location.line,
+            "startColumn": location.column,
+            "endLine": location.end_line,
+            "endColumn": location.end_column,
+        },
+    })
+}
@@ -0,0 +1,166 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::Context;
+use azhdaha_errors::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+/// Identifies a finding without depending on the line which it is found at, so it is still
+/// recognized after the code around it is changed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct Fingerprint {
+    file: String,
+    function: Option<String>,
+    code: String,
+    variable: Option<String>,
+    /// The first line of the reported code with its whitespaces collapsed.
+    snippet: String,
+}
+
+impl Fingerprint {
+    pub(crate) fn new(diagnostic: &Diagnostic) -> Self {
+        Self {
+            file: diagnostic.location.file.clone(),
+            function: diagnostic.function.clone(),
+            code: diagnostic.code.unwrap_or_default().to_owned(),
+            variable: diagnostic.variable.clone(),
+            snippet: diagnostic
+                .snippet
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// The findings which were accepted when the checker was adopted, along with the number of
+/// times each of them was found.
+#[derive(Debug, Default)]
+pub(crate) struct Baseline {
+    counts: HashMap<Fingerprint, usize>,
+}
+
+impl Baseline {
+    pub(crate) fn read(path: &Path) -> anyhow::Result<Self> {
+        let fingerprints: Vec<Fingerprint> = serde_json::from_str(
+            &fs::read_to_string(path).context("Failed to read the baseline.")?,
+        )
+        .context("Failed to parse the baseline.")?;
+
+        Ok(fingerprints.into_iter().collect())
+    }
+
+    /// Returns whether the finding is recorded in the baseline, while consuming one of its
+    /// records, so a finding which is repeated more times than recorded is still new.
+    pub(crate) fn accepts(&mut self, fingerprint: &Fingerprint) -> bool {
+        match self.counts.get_mut(fingerprint) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromIterator<Fingerprint> for Baseline {
+    fn from_iter<T: IntoIterator<Item = Fingerprint>>(fingerprints: T) -> Self {
+        let mut baseline = Self::default();
+
+        for fingerprint in fingerprints {
+            *baseline.counts.entry(fingerprint).or_default() += 1;
+        }
+
+        baseline
+    }
+}
+
+pub(crate) fn write(path: &Path, fingerprints: &[Fingerprint]) -> anyhow::Result<()> {
+    let mut content = serde_json::to_string_pretty(fingerprints)?;
+    content.push('\n');
+
+    fs::write(path, content).context("Failed to write the baseline.")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{env, fs, process, slice};
+
+    use azhdaha_errors::{Diagnostic, Location, Severity, Span, Stage};
+
+    use super::{Baseline, Fingerprint, write};
+
+    pub(crate) fn diagnostic(function: &str, line: usize, snippet: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            stage: Stage::Analysis,
+            code: Some("AZ0001"),
+            kind: Some("memory_leakage"),
+            message: "The memory is leaked.".to_owned(),
+            location: Location {
+                file: "main.c".to_owned(),
+                line,
+                column: 5,
+                end_line: line,
+                end_column: 10,
+                lo: None,
+                hi: None,
+                span: Span::DUMMY,
+            },
+            function: Some(function.to_owned()),
+            variable: Some("ptr".to_owned()),
+            snippet: snippet.to_owned(),
+            labels: vec![],
+            notes: vec![],
+            helps: vec![],
+            function_line: Some(1),
+        }
+    }
+
+    #[test]
+    fn matches_the_findings_which_are_moved_or_reformatted() {
+        let recorded = diagnostic("main", 3, "copy(ptr, len);");
+        let moved = diagnostic("main", 7, "\tcopy(ptr,  len);");
+
+        let mut baseline: Baseline = [Fingerprint::new(&recorded)].into_iter().collect();
+
+        assert!(baseline.accepts(&Fingerprint::new(&moved)));
+    }
+
+    #[test]
+    fn distinguishes_the_findings_of_other_functions_and_snippets() {
+        let mut baseline: Baseline = [Fingerprint::new(&diagnostic("main", 3, "free(ptr);"))]
+            .into_iter()
+            .collect();
+
+        assert!(!baseline.accepts(&Fingerprint::new(&diagnostic("helper", 3, "free(ptr);"))));
+        assert!(!baseline.accepts(&Fingerprint::new(&diagnostic("main", 3, "use(ptr);"))));
+    }
+
+    #[test]
+    fn accepts_a_finding_as_many_times_as_it_is_recorded() {
+        let fingerprint = Fingerprint::new(&diagnostic("main", 3, "free(ptr);"));
+        let mut baseline: Baseline = [fingerprint.clone(), fingerprint.clone()]
+            .into_iter()
+            .collect();
+
+        assert!(baseline.accepts(&fingerprint));
+        assert!(baseline.accepts(&fingerprint));
+        assert!(!baseline.accepts(&fingerprint));
+    }
+
+    #[test]
+    fn reads_the_written_baseline() {
+        let path = env::temp_dir().join(format!("azhdaha-baseline-{}.json", process::id()));
+        let fingerprint = Fingerprint::new(&diagnostic("main", 3, "free(ptr);"));
+
+        write(&path, slice::from_ref(&fingerprint)).unwrap();
+        let mut baseline = Baseline::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(baseline.accepts(&fingerprint));
+        assert!(!baseline.accepts(&fingerprint));
+    }
+}
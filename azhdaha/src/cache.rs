@@ -0,0 +1,455 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use analyzer::{FuncSummary, ViolationKind};
+use anyhow::Context;
+use ast_utils::AstRepr;
+use azhdaha_errors::{Diagnostic, Label, Location, Severity, Span, Stage};
+use log::{debug, warn};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CheckOptions,
+    incremental::{FuncResult, IncrementalCheck},
+};
+
+/// The directory under the data directory of azhdaha which the results are cached in.
+const CACHE_PATH: &str = "cache/";
+
+/// The number of the temporary files which the results have been written to, which makes
+/// their names unique within the process.
+static TEMPORARY_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Caches the results of checking the entries on the disk, so an entry whose preprocessed
+/// source code is not changed is not checked again, and only the changed functions of the
+/// other entries are checked again. The results of the entries are keyed by the hash of the
+/// preprocessed source code, the annotated headers, the version of the tool and the options,
+/// while the results of the functions are keyed by the hash of their text, and are only
+/// reused while the source code outside of the functions and the summaries of the functions
+/// are not changed.
+#[derive(Debug)]
+pub struct Cache {
+    directory: PathBuf,
+    /// The hash of the annotated headers, the version of the tool and the options, which the
+    /// results of the entries are only reused under.
+    environment: u64,
+}
+
+/// The results of checking an entry which are stored in its own file.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    environment: u64,
+    /// The hash of the preprocessed source code and the environment.
+    key: u64,
+    /// The hash of the source code outside of the function definitions.
+    context_hash: u64,
+    /// The diagnostics of the entry after being suppressed.
+    diagnostics: Vec<CachedDiagnostic>,
+    funcs: Vec<CachedFunc>,
+    summaries: HashMap<String, FuncSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFunc {
+    span: (usize, usize),
+    /// The hash of the text of the function definition.
+    hash: u64,
+    lowering_diagnostic: Option<CachedDiagnostic>,
+    diagnostics: Vec<CachedDiagnostic>,
+}
+
+/// A diagnostic along with the parts which are left out of the reports, such as the spans of
+/// its locations in the preprocessed source code.
+#[derive(Serialize, Deserialize)]
+struct CachedDiagnostic {
+    severity: Severity,
+    stage: Stage,
+    code: Option<String>,
+    message: String,
+    location: CachedLocation,
+    function: Option<String>,
+    variable: Option<String>,
+    snippet: String,
+    labels: Vec<(String, CachedLocation)>,
+    notes: Vec<String>,
+    helps: Vec<String>,
+    function_line: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedLocation {
+    file: String,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    lo: Option<usize>,
+    hi: Option<usize>,
+    span: (usize, usize),
+}
+
+impl Cache {
+    /// Opens the cache in the directory, or in the data directory of azhdaha when no
+    /// directory is given, for the results of checking the entries with the options.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if the directory cannot be created.
+    ///
+    pub fn open(directory: Option<PathBuf>, options: &CheckOptions) -> anyhow::Result<Self> {
+        let directory = match directory {
+            Some(directory) => directory,
+            None => directories::ProjectDirs::from("ir", "skye-tan", "azhdaha")
+                .context("Failed to find the data directory of azhdaha.")?
+                .data_dir()
+                .join(CACHE_PATH),
+        };
+
+        fs::create_dir_all(&directory).with_context(|| {
+            format!(
+                "Failed to create the cache directory '{}'.",
+                directory.display()
+            )
+        })?;
+
+        let mut hasher = DefaultHasher::new();
+
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        options.allow_exit_leaks.hash(&mut hasher);
+
+        // The headers which cannot be read are only missed by the cache.
+        if let Ok(lib_dir) = ast_utils::annotated_lib_dir()
+            && let Err(error) = hash_dir(&lib_dir, &mut hasher)
+        {
+            debug!("Failed to hash the annotated headers - {error:?}");
+        }
+
+        Ok(Self {
+            directory,
+            environment: hasher.finish(),
+        })
+    }
+
+    /// Checks the entries like [`crate::check_entries`] without analyzing them together,
+    /// while reusing the cached results of the entries and their functions.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if the preprocessed source code of an entry is not valid UTF-8.
+    ///
+    pub fn check_entries(
+        &self,
+        ast_reprs: &[AstRepr],
+        options: &CheckOptions,
+    ) -> anyhow::Result<Vec<Vec<Diagnostic>>> {
+        ast_reprs
+            .par_iter()
+            .map(|ast_repr| self.check(ast_repr, options))
+            .collect()
+    }
+
+    /// Checks the entry while reusing its cached results.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if the preprocessed source code of the entry is not valid UTF-8.
+    ///
+    pub fn check(
+        &self,
+        ast_repr: &AstRepr,
+        options: &CheckOptions,
+    ) -> anyhow::Result<Vec<Diagnostic>> {
+        let code = str::from_utf8(&ast_repr.source_info.code)
+            .context("UTF-8 validity for the source code failed.")?;
+
+        let mut hasher = DefaultHasher::new();
+        self.environment.hash(&mut hasher);
+        code.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let path = self.entry_path(ast_repr);
+
+        let cache_entry = match fs::read(&path) {
+            Ok(content) => serde_json::from_slice::<CacheEntry>(&content)
+                .inspect_err(|error| debug!("Failed to read '{}' - {error}", path.display()))
+                .ok(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => {
+                debug!("Failed to read '{}' - {error}", path.display());
+                None
+            }
+        };
+
+        let mut check = match cache_entry {
+            Some(cache_entry) if cache_entry.key == key => {
+                debug!("Reused the results of '{}'.", ast_repr.source_info.path);
+
+                return Ok(cache_entry
+                    .diagnostics
+                    .into_iter()
+                    .map(CachedDiagnostic::into_diagnostic)
+                    .collect());
+            }
+            Some(cache_entry) if cache_entry.environment == self.environment => {
+                cache_entry.into_check()
+            }
+            _ => IncrementalCheck::new(),
+        };
+
+        // The functions are matched by the hashes of their text, since the edit to the source
+        // code is not known.
+        let diagnostics = check.check(ast_repr, None, options)?;
+
+        let cache_entry = CacheEntry {
+            environment: self.environment,
+            key,
+            context_hash: check.context_hash,
+            diagnostics: diagnostics.iter().map(CachedDiagnostic::from).collect(),
+            funcs: check
+                .funcs
+                .iter()
+                .map(|func| CachedFunc {
+                    span: (func.span.lo, func.span.hi),
+                    hash: func.hash,
+                    lowering_diagnostic: func.lowering_diagnostic.as_ref().map(Into::into),
+                    diagnostics: func.diagnostics.iter().map(Into::into).collect(),
+                })
+                .collect(),
+            summaries: check.summaries,
+        };
+
+        // The results are written to a temporary file which then replaces the cached ones, so
+        // the entries of the same file which are checked at the same time cannot mix their
+        // results. The results which cannot be cached are only checked again by the next run.
+        let temporary_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            process::id(),
+            TEMPORARY_FILE_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        if let Err(error) = serde_json::to_vec(&cache_entry)
+            .map_err(io::Error::from)
+            .and_then(|content| fs::write(&temporary_path, content))
+            .and_then(|()| fs::rename(&temporary_path, &path))
+        {
+            warn!("Failed to write '{}' - {error}", path.display());
+            fs::remove_file(&temporary_path).ok();
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Returns the file which the results of the entry are cached in, which is named by the
+    /// hash of the entry's path and directory.
+    fn entry_path(&self, ast_repr: &AstRepr) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        ast_repr.source_info.path.hash(&mut hasher);
+        ast_repr.source_info.directory.hash(&mut hasher);
+
+        self.directory
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+}
+
+impl CacheEntry {
+    fn into_check(self) -> IncrementalCheck {
+        IncrementalCheck {
+            funcs: self
+                .funcs
+                .into_iter()
+                .map(|func| FuncResult {
+                    span: Span {
+                        lo: func.span.0,
+                        hi: func.span.1,
+                    },
+                    hash: func.hash,
+                    lowering_diagnostic: func
+                        .lowering_diagnostic
+                        .map(CachedDiagnostic::into_diagnostic),
+                    diagnostics: func
+                        .diagnostics
+                        .into_iter()
+                        .map(CachedDiagnostic::into_diagnostic)
+                        .collect(),
+                })
+                .collect(),
+            context_hash: self.context_hash,
+            summaries: self.summaries,
+        }
+    }
+}
+
+impl From<&Diagnostic> for CachedDiagnostic {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        Self {
+            severity: diagnostic.severity,
+            stage: diagnostic.stage,
+            code: diagnostic.code.map(str::to_owned),
+            message: diagnostic.message.clone(),
+            location: (&diagnostic.location).into(),
+            function: diagnostic.function.clone(),
+            variable: diagnostic.variable.clone(),
+            snippet: diagnostic.snippet.clone(),
+            labels: diagnostic
+                .labels
+                .iter()
+                .map(|label| (label.message.clone(), (&label.location).into()))
+                .collect(),
+            notes: diagnostic.notes.clone(),
+            helps: diagnostic.helps.clone(),
+            function_line: diagnostic.function_line,
+        }
+    }
+}
+
+impl CachedDiagnostic {
+    fn into_diagnostic(self) -> Diagnostic {
+        let kind = self.code.as_deref().and_then(ViolationKind::from_code);
+
+        Diagnostic {
+            severity: self.severity,
+            stage: self.stage,
+            code: kind.map(ViolationKind::code),
+            kind: kind.map(ViolationKind::name),
+            message: self.message,
+            location: self.location.into(),
+            function: self.function,
+            variable: self.variable,
+            snippet: self.snippet,
+            labels: self
+                .labels
+                .into_iter()
+                .map(|(message, location)| Label {
+                    message,
+                    location: location.into(),
+                })
+                .collect(),
+            notes: self.notes,
+            helps: self.helps,
+            function_line: self.function_line,
+        }
+    }
+}
+
+impl From<&Location> for CachedLocation {
+    fn from(location: &Location) -> Self {
+        Self {
+            file: location.file.clone(),
+            line: location.line,
+            column: location.column,
+            end_line: location.end_line,
+            end_column: location.end_column,
+            lo: location.lo,
+            hi: location.hi,
+            span: (location.span.lo, location.span.hi),
+        }
+    }
+}
+
+impl From<CachedLocation> for Location {
+    fn from(location: CachedLocation) -> Self {
+        Self {
+            file: location.file,
+            line: location.line,
+            column: location.column,
+            end_line: location.end_line,
+            end_column: location.end_column,
+            lo: location.lo,
+            hi: location.hi,
+            span: Span {
+                lo: location.span.0,
+                hi: location.span.1,
+            },
+        }
+    }
+}
+
+/// Hashes the names and the contents of the files in the directory and its subdirectories.
+fn hash_dir(directory: &Path, hasher: &mut DefaultHasher) -> io::Result<()> {
+    let mut paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    paths.sort();
+
+    for path in paths {
+        path.file_name().hash(hasher);
+
+        if path.is_dir() {
+            hash_dir(&path, hasher)?;
+        } else {
+            fs::read(&path)?.hash(hasher);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use ast_utils::AstRepr;
+
+    use crate::{CheckOptions, IncrementalCheck};
+
+    use super::Cache;
+
+    const CODE: &str = "// azhdaha-allow(leak): the value lives until the program exits.\nint helper(int *ptr) {\n    return *ptr;\n}\n\nint main() {\n    return 0;\n}\n";
+
+    /// Returns a directory for the cache of the test, which is not shared with the other
+    /// tests.
+    fn cache_dir(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("azhdaha-cache-{}-{name}", process::id()))
+    }
+
+    #[test]
+    fn warm_rerun_returns_identical_diagnostics() {
+        let directory = cache_dir("warm");
+        let options = CheckOptions::default();
+        let cache = Cache::open(Some(directory.clone()), &options).unwrap();
+        let ast_repr = AstRepr::new_single_file(CODE).unwrap();
+
+        let cold_diagnostics = cache.check(&ast_repr, &options).unwrap();
+        let warm_diagnostics = cache.check(&ast_repr, &options).unwrap();
+
+        fs::remove_dir_all(&directory).ok();
+
+        assert!(!cold_diagnostics.is_empty());
+        assert_eq!(cold_diagnostics, warm_diagnostics);
+    }
+
+    #[test]
+    fn changed_entry_matches_the_check_from_scratch() {
+        let directory = cache_dir("changed");
+        let options = CheckOptions::default();
+        let cache = Cache::open(Some(directory.clone()), &options).unwrap();
+        let mut ast_repr = AstRepr::new_single_file(CODE).unwrap();
+
+        cache.check(&ast_repr, &options).unwrap();
+
+        let start = CODE.find("return 0").unwrap();
+        ast_repr
+            .edit(start..start + "return 0".len(), "return helper(0)")
+            .unwrap();
+
+        let cached_diagnostics = cache.check(&ast_repr, &options).unwrap();
+        let scratch_diagnostics = IncrementalCheck::new()
+            .check(&ast_repr, None, &options)
+            .unwrap();
+
+        fs::remove_dir_all(&directory).ok();
+
+        assert_eq!(cached_diagnostics, scratch_diagnostics);
+    }
+}
@@ -0,0 +1,323 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use analyzer::{FuncSummary, LinearCtx};
+use ast_utils::{AstRepr, SourceEdit};
+use azhdaha_errors::{Diagnostic, Label, Location, Source, Span, Stage};
+use log::debug;
+use repr::hir::{self, HirCtx, resolver::SymbolKind};
+
+use crate::{CheckOptions, lower_funcs, own_lowering_diagnostics};
+
+/// The results of checking a function definition, which are reused while it is not changed.
+#[derive(Debug, Clone)]
+pub(crate) struct FuncResult {
+    /// The range of the definition in the preprocessed source code.
+    pub(crate) span: Span,
+    /// The hash of the text of the definition, which its results are found by when the
+    /// source code is changed by an unknown edit.
+    pub(crate) hash: u64,
+    /// The diagnostic of the function when it failed to be lowered to the MIR.
+    pub(crate) lowering_diagnostic: Option<Diagnostic>,
+    /// The diagnostics which are found by analyzing the function, before being suppressed.
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+/// Checks an entry again after it is edited, while only the function definitions which are
+/// changed by the edit are lowered to the MIR and analyzed again. The results of the other
+/// function definitions are reused, unless the edit changes anything outside of a function
+/// definition or the summaries of the functions, which makes the entry be checked from
+/// scratch.
+#[derive(Debug, Default)]
+pub struct IncrementalCheck {
+    /// The results of the function definitions of the last check in the order of their
+    /// definition.
+    pub(crate) funcs: Vec<FuncResult>,
+    /// The hash of the source code outside of the function definitions, which the results of
+    /// the function definitions depend on along with the summaries of the functions.
+    pub(crate) context_hash: u64,
+    pub(crate) summaries: HashMap<String, FuncSummary>,
+}
+
+impl IncrementalCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the entry and returns its diagnostics like [`crate::check_entries`], where the
+    /// edit is the one which the entry was updated by since the last check. When there is no
+    /// such edit, the results of the function definitions whose text is not changed are
+    /// reused, as long as the source code outside of the function definitions is not changed
+    /// either.
+    ///
+    /// # Errors
+    ///
+    /// Return [`Err`] if the preprocessed source code of the entry is not valid UTF-8.
+    ///
+    pub fn check(
+        &mut self,
+        ast_repr: &AstRepr,
+        edit: Option<&SourceEdit>,
+        options: &CheckOptions,
+    ) -> anyhow::Result<Vec<Diagnostic>> {
+        let source = &ast_repr.source_info;
+        let (func_hashes, context_hash) = hash_funcs(ast_repr);
+
+        // The results of the unchanged function definitions by their range after the edit.
+        let reused_funcs: HashMap<Span, FuncResult> = match edit {
+            Some(edit)
+                if edit.is_empty() || self.funcs.iter().any(|func| edit.is_inside(func.span)) =>
+            {
+                self.funcs
+                    .iter()
+                    .filter_map(|func| relocate(func, |span| edit.map(span), source))
+                    .map(|func| (func.span, func))
+                    .collect()
+            }
+            None if context_hash == self.context_hash => func_hashes
+                .iter()
+                .filter_map(|(new_span, hash)| {
+                    let func = self.funcs.iter().find(|func| func.hash == *hash)?;
+
+                    // The spans in the definition are moved along with it.
+                    let shift = |span: Span| {
+                        (func.span.lo <= span.lo && span.hi <= func.span.hi).then(|| Span {
+                            lo: span.lo - func.span.lo + new_span.lo,
+                            hi: span.hi - func.span.lo + new_span.lo,
+                        })
+                    };
+
+                    relocate(func, shift, source)
+                })
+                .map(|func| (func.span, func))
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        let (items, symbol_resolver, type_tag_resolver, hir_diagnostics) = HirCtx::new(ast_repr)
+            .skip_func_defs(reused_funcs.keys().copied().collect())
+            .lower_to_hir();
+
+        let mut linear_ctx = LinearCtx::new(&ast_repr.source_info, options.allow_exit_leaks)?;
+
+        let mut mir_results = lower_funcs(&items, &symbol_resolver, &type_tag_resolver).into_iter();
+        let mut mir_bodies = vec![];
+
+        // The results of the function definitions along with the index of the MIR body of the
+        // changed ones.
+        let mut funcs = vec![];
+        // The names of the unchanged function definitions.
+        let mut reused_names = HashSet::new();
+
+        for item in &items {
+            match &item.kind {
+                hir::ItemKind::Func(_) => match mir_results.next() {
+                    Some(Ok(mir_body)) => {
+                        funcs.push((
+                            FuncResult {
+                                span: item.span,
+                                hash: hash_of(ast_repr, item.span),
+                                lowering_diagnostic: None,
+                                diagnostics: vec![],
+                            },
+                            Some(mir_bodies.len()),
+                        ));
+                        mir_bodies.push(mir_body);
+                    }
+                    Some(Err(error)) => funcs.push((
+                        FuncResult {
+                            span: item.span,
+                            hash: hash_of(ast_repr, item.span),
+                            lowering_diagnostic: Some(
+                                error.to_diagnostic(Stage::Mir, &ast_repr.source_info),
+                            ),
+                            diagnostics: vec![],
+                        },
+                        None,
+                    )),
+                    None => (),
+                },
+                hir::ItemKind::Decl(symbols) if let Some(func) = reused_funcs.get(&item.span) => {
+                    funcs.push((func.clone(), None));
+
+                    reused_names.extend(symbols.iter().filter_map(|symbol| {
+                        match symbol_resolver.get_data_by_res(symbol) {
+                            SymbolKind::Func(func_decl) => Some(func_decl.ident.name.clone()),
+                            _ => None,
+                        }
+                    }));
+                }
+                _ => (),
+            }
+        }
+
+        // Only the summaries of the unchanged functions are known, so the summaries of the
+        // functions which are removed or renamed by the edit are left out.
+        let known_summaries: HashMap<_, _> = self
+            .summaries
+            .iter()
+            .filter(|(name, _)| reused_names.contains(*name))
+            .map(|(name, summary)| (name.clone(), summary.clone()))
+            .collect();
+
+        linear_ctx.infer_summaries_with(&mir_bodies, &known_summaries);
+
+        // The results of the unchanged functions depend on the summaries of the changed ones.
+        if !reused_funcs.is_empty() && *linear_ctx.summaries() != self.summaries {
+            *self = Self::new();
+
+            return self.check(ast_repr, None, options);
+        }
+
+        for (func, mir_index) in &mut funcs {
+            if let Some(mir_index) = mir_index {
+                func.diagnostics = linear_ctx.analyze(&mir_bodies[*mir_index]);
+            }
+        }
+
+        debug!(
+            "Reused the results of {} function definitions of '{}'.",
+            reused_funcs.len(),
+            ast_repr.source_info.path
+        );
+
+        self.funcs = funcs.into_iter().map(|(func, _)| func).collect();
+        self.context_hash = context_hash;
+        self.summaries = linear_ctx.summaries().clone();
+
+        let diagnostics = self
+            .funcs
+            .iter()
+            .flat_map(|func| func.diagnostics.iter().cloned())
+            .collect();
+
+        let mut all_diagnostics = own_lowering_diagnostics(
+            ast_repr,
+            hir_diagnostics.into_iter().chain(
+                self.funcs
+                    .iter()
+                    .filter_map(|func| func.lowering_diagnostic.clone()),
+            ),
+        );
+        all_diagnostics.extend(linear_ctx.suppress(&ast_repr.source_info.comments, diagnostics));
+
+        Ok(all_diagnostics)
+    }
+}
+
+/// Returns the spans of the function definitions along with the hashes of their text, and
+/// the hash of the source code outside of them.
+fn hash_funcs(ast_repr: &AstRepr) -> (Vec<(Span, u64)>, u64) {
+    let code = &ast_repr.source_info.code;
+    let spans = ast_repr.func_def_spans();
+
+    let mut hasher = DefaultHasher::new();
+    let mut context_start = 0;
+
+    for span in &spans {
+        code[context_start..span.lo].hash(&mut hasher);
+        context_start = span.hi;
+    }
+
+    code[context_start..].hash(&mut hasher);
+
+    (
+        spans
+            .into_iter()
+            .map(|span| (span, hash_of(ast_repr, span)))
+            .collect(),
+        hasher.finish(),
+    )
+}
+
+/// Returns the hash of the text of the span.
+fn hash_of(ast_repr: &AstRepr, span: Span) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ast_repr.source_info.code[span.lo..span.hi].hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Moves the results of the unchanged function definition to where it is after the change,
+/// where the spans before the change are mapped to the spans after it. It is [`None`] when
+/// one of its diagnostics is changed, which makes the definition be analyzed again.
+fn relocate(
+    func: &FuncResult,
+    map: impl Fn(Span) -> Option<Span>,
+    source: &dyn Source,
+) -> Option<FuncResult> {
+    let relocate_location = |location: &Location| Some(source.location(map(location.span)?));
+
+    let relocate_diagnostic = |diagnostic: &Diagnostic| {
+        let location = relocate_location(&diagnostic.location)?;
+
+        Some(Diagnostic {
+            // The lines of the function are shifted together.
+            function_line: diagnostic
+                .function_line
+                .and_then(|line| (line + location.line).checked_sub(diagnostic.location.line)),
+            labels: diagnostic
+                .labels
+                .iter()
+                .map(|label| {
+                    Some(Label {
+                        message: label.message.clone(),
+                        location: relocate_location(&label.location)?,
+                    })
+                })
+                .collect::<Option<_>>()?,
+            location,
+            ..diagnostic.clone()
+        })
+    };
+
+    Some(FuncResult {
+        span: map(func.span)?,
+        hash: func.hash,
+        lowering_diagnostic: match &func.lowering_diagnostic {
+            Some(diagnostic) => Some(relocate_diagnostic(diagnostic)?),
+            None => None,
+        },
+        diagnostics: func
+            .diagnostics
+            .iter()
+            .map(relocate_diagnostic)
+            .collect::<Option<_>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ast_utils::AstRepr;
+
+    use crate::CheckOptions;
+
+    use super::IncrementalCheck;
+
+    const CODE: &str =
+        "int helper(int *ptr) {\n    return *ptr;\n}\n\nint main() {\n    return 0;\n}\n";
+
+    #[test]
+    fn drops_the_summaries_of_the_renamed_functions() {
+        let options = CheckOptions::default();
+        let mut ast_repr = AstRepr::new_single_file(CODE).unwrap();
+        let mut check = IncrementalCheck::new();
+
+        check.check(&ast_repr, None, &options).unwrap();
+        assert!(check.summaries.contains_key("helper"));
+
+        let start = CODE.find("helper").unwrap();
+        let edit = ast_repr
+            .edit(start..start + "helper".len(), "walker")
+            .unwrap();
+
+        check.check(&ast_repr, Some(&edit), &options).unwrap();
+        assert!(!check.summaries.contains_key("helper"));
+        assert!(check.summaries.contains_key("walker"));
+        assert!(check.summaries.contains_key("main"));
+    }
+}
@@ -0,0 +1,418 @@
+//! The pipeline which lowers the C source code to the MIR and applies the linear rules to
+//! it, so the checker can be embedded in other tools.
+//!
+//! ```no_run
+//! let options = azhdaha::CheckOptions::default();
+//!
+//! let diagnostics = azhdaha::check_source(
+//!     "#include <azhdaha.h>\n#include <stdlib.h>\n\nvoid leak(void) { LINEAR_TYPE int *ptr = malloc(4); }\n",
+//!     &options,
+//! )?;
+//!
+//! assert!(diagnostics.iter().any(|diagnostic| diagnostic.code == Some("AZ0001")));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+
+use std::slice;
+
+use analyzer::{FuncDefs, LinearCtx, Ownership};
+use ast_utils::AstRepr;
+use azhdaha_errors::{Diagnostic, Stage};
+use compile_commands::CompilationDatabase;
+use log::info;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use repr::{
+    hir::{
+        self, HirCtx,
+        resolver::{CompoundTypeData, Resolver, SymbolKind},
+    },
+    mir::{self, MirCtx},
+};
+
+/// Contains the cache of the results of the entries on the disk.
+mod cache;
+/// Contains the checking of the entries which reuses the results of their unchanged functions.
+mod incremental;
+
+pub use cache::Cache;
+pub use incremental::IncrementalCheck;
+
+/// The path which the diagnostics of the source code given by [`check_source`] are reported
+/// at, unless another one is named by [`CheckOptions::path`].
+const DEFAULT_SOURCE_PATH: &str = "<source>";
+
+/// Determines how the source code is preprocessed and analyzed.
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    /// The path which the diagnostics of the source code given by [`check_source`] are
+    /// reported at.
+    pub path: Option<String>,
+    /// The flags which are passed to the preprocessor along with the source code given by
+    /// [`check_source`], such as `-I` and `-D` flags.
+    pub flags: Vec<String>,
+    /// Whether the linear values which are still owned when the program exits are allowed.
+    pub allow_exit_leaks: bool,
+    /// Whether the entries are analyzed together, so the functions defined in each of them
+    /// are known to the others.
+    pub whole_program: bool,
+}
+
+/// The diagnostics of the entries of compile-commands.
+#[derive(Debug, Clone, Default)]
+pub struct CompileDbDiagnostics {
+    pub diagnostics: Vec<Diagnostic>,
+    /// The number of the entries which failed to be preprocessed or parsed, so they have no
+    /// diagnostics.
+    pub failed_entry_count: u32,
+}
+
+/// Checks the C source code which is given as a string and returns its diagnostics, including
+/// the diagnostics of its items which failed to be lowered.
+///
+/// # Errors
+///
+/// Return [`Err`] if the source code fails to be preprocessed or parsed.
+///
+pub fn check_source(code: &str, options: &CheckOptions) -> anyhow::Result<Vec<Diagnostic>> {
+    let path = options.path.as_deref().unwrap_or(DEFAULT_SOURCE_PATH);
+    let ast_repr = AstRepr::from_source(path, code, &options.flags)?;
+
+    let diagnostics = check_entries(slice::from_ref(&ast_repr), options)?;
+
+    Ok(diagnostics.into_iter().flatten().collect())
+}
+
+/// Checks the entries of compile-commands and returns their diagnostics.
+///
+/// # Errors
+///
+/// Return [`Err`] if the C grammar fails to be loaded or the preprocessed source code of an
+/// entry is not valid UTF-8.
+///
+pub fn check_compile_db(
+    compile_commands: &CompilationDatabase,
+    options: &CheckOptions,
+) -> anyhow::Result<CompileDbDiagnostics> {
+    let (ast_reprs, failed_entry_count) = AstRepr::construct(compile_commands)?;
+
+    let diagnostics = check_entries(&ast_reprs, options)?;
+
+    Ok(CompileDbDiagnostics {
+        diagnostics: diagnostics.into_iter().flatten().collect(),
+        failed_entry_count,
+    })
+}
+
+/// Checks the parsed entries and returns the diagnostics of each of them in the order of the
+/// entries, where the diagnostics of the items which failed to be lowered in the files of the
+/// entries come first, while the items of the included headers are left out silently. Unless
+/// they are analyzed together, the entries are checked in parallel by the threads of the
+/// current rayon thread pool.
+///
+/// # Errors
+///
+/// Return [`Err`] if the preprocessed source code of an entry is not valid UTF-8.
+///
+pub fn check_entries(
+    ast_reprs: &[AstRepr],
+    options: &CheckOptions,
+) -> anyhow::Result<Vec<Vec<Diagnostic>>> {
+    if options.whole_program {
+        return check_together(ast_reprs, options);
+    }
+
+    let diagnostics = ast_reprs
+        .par_iter()
+        .map(|ast_repr| check_together(slice::from_ref(ast_repr), options))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(diagnostics.into_iter().flatten().collect())
+}
+
+/// Lowers the functions of the entry to the MIR and hands each of the MIR bodies to `inspect`
+/// in the order of their definitions. The functions which fail to be lowered are left out.
+pub fn inspect_mir(ast_repr: &AstRepr, mut inspect: impl FnMut(&mir::Body)) {
+    let (items, symbol_resolver, type_tag_resolver, _) = HirCtx::new(ast_repr).lower_to_hir();
+
+    for mir_body in lower_funcs(&items, &symbol_resolver, &type_tag_resolver)
+        .iter()
+        .flatten()
+    {
+        inspect(mir_body);
+    }
+}
+
+/// Analyzes the entries together, so the functions defined in each of them are known to the
+/// others.
+fn check_together(
+    ast_reprs: &[AstRepr],
+    options: &CheckOptions,
+) -> anyhow::Result<Vec<Vec<Diagnostic>>> {
+    let hir_reprs: Vec<_> = ast_reprs
+        .par_iter()
+        .map(|ast_repr| HirCtx::new(ast_repr).lower_to_hir())
+        .collect();
+
+    let mut linear_ctxs = ast_reprs
+        .iter()
+        .map(|ast_repr| LinearCtx::new(&ast_repr.source_info, options.allow_exit_leaks))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // The MIR bodies of all the entries along with the index of the entry which defines them.
+    let mut entry_indices = vec![];
+    let mut mir_bodies = vec![];
+    // The diagnostics of the items which failed to be lowered in each of the entries.
+    let mut lowering_diagnostics = vec![vec![]; ast_reprs.len()];
+
+    for (index, (items, symbol_resolver, type_tag_resolver, diagnostics)) in
+        hir_reprs.iter().enumerate()
+    {
+        lowering_diagnostics[index].extend(diagnostics.iter().cloned());
+
+        for result in lower_funcs(items, symbol_resolver, type_tag_resolver) {
+            match result {
+                Ok(mir_body) => {
+                    entry_indices.push(index);
+                    mir_bodies.push(mir_body);
+                }
+                Err(error) => {
+                    lowering_diagnostics[index]
+                        .push(error.to_diagnostic(Stage::Mir, &ast_reprs[index].source_info));
+                }
+            }
+        }
+    }
+
+    let Some((program_ctx, entry_ctxs)) = linear_ctxs.split_first_mut() else {
+        return Ok(vec![]);
+    };
+
+    // The summaries of the functions are needed before analyzing the calls to them.
+    program_ctx.infer_summaries(&mir_bodies);
+
+    for entry_ctx in entry_ctxs {
+        entry_ctx.import_summaries(program_ctx);
+    }
+
+    let mut diagnostics = vec![vec![]; ast_reprs.len()];
+
+    if options.whole_program {
+        let mut func_defs = FuncDefs::new();
+
+        for (ast_repr, (items, symbol_resolver, ..)) in ast_reprs.iter().zip(&hir_reprs) {
+            func_defs.collect(&ast_repr.source_info.path, items, symbol_resolver);
+        }
+
+        for (index, (items, symbol_resolver, ..)) in hir_reprs.iter().enumerate() {
+            diagnostics[index].extend(linear_ctxs[index].check_declarations(
+                items,
+                symbol_resolver,
+                &func_defs,
+            ));
+        }
+    }
+
+    let body_diagnostics: Vec<_> = mir_bodies
+        .par_iter()
+        .zip(entry_indices.par_iter())
+        .flat_map_iter(|(mir_body, &index)| {
+            linear_ctxs[index]
+                .analyze(mir_body)
+                .into_iter()
+                .map(move |diagnostic| (index, diagnostic))
+        })
+        .collect();
+
+    for (index, diagnostic) in body_diagnostics {
+        diagnostics[index].push(diagnostic);
+    }
+
+    Ok(ast_reprs
+        .iter()
+        .zip(&linear_ctxs)
+        .zip(diagnostics)
+        .zip(lowering_diagnostics)
+        .map(
+            |(((ast_repr, linear_ctx), diagnostics), lowering_diagnostics)| {
+                let mut lowering_diagnostics =
+                    own_lowering_diagnostics(ast_repr, lowering_diagnostics);
+
+                lowering_diagnostics
+                    .extend(linear_ctx.suppress(&ast_repr.source_info.comments, diagnostics));
+
+                lowering_diagnostics
+            },
+        )
+        .collect())
+}
+
+/// Returns the ownership status of the linear variables right before each statement of the
+/// functions of the entry, which is queried by the offsets of the preprocessed source code.
+///
+/// # Errors
+///
+/// Return [`Err`] if the preprocessed source code of the entry is not valid UTF-8.
+///
+pub fn ownership(ast_repr: &AstRepr, options: &CheckOptions) -> anyhow::Result<Vec<Ownership>> {
+    let (items, symbol_resolver, type_tag_resolver, _) = HirCtx::new(ast_repr).lower_to_hir();
+
+    let mut linear_ctx = LinearCtx::new(&ast_repr.source_info, options.allow_exit_leaks)?;
+
+    let mir_bodies: Vec<_> = lower_funcs(&items, &symbol_resolver, &type_tag_resolver)
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect();
+
+    // The summaries of the functions are needed to know which calls move the variables.
+    linear_ctx.infer_summaries(&mir_bodies);
+
+    Ok(mir_bodies
+        .par_iter()
+        .map(|mir_body| linear_ctx.ownership(mir_body))
+        .collect())
+}
+
+/// Returns the diagnostics of the items which failed to be lowered in the file of the entry.
+/// The items of the included headers, such as the typedefs of the standard library which
+/// use the builtin types of the compiler, are left out of the analysis without being
+/// reported, since they are not found in the code which is checked. They are only logged.
+fn own_lowering_diagnostics(
+    ast_repr: &AstRepr,
+    diagnostics: impl IntoIterator<Item = Diagnostic>,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            let location = &diagnostic.location;
+
+            if location.file == ast_repr.source_info.path {
+                return true;
+            }
+
+            info!(
+                "The item at {}:{} is left out of the analysis - {}",
+                location.file, location.line, diagnostic.message
+            );
+
+            false
+        })
+        .collect()
+}
+
+/// Lowers the functions among the items to the MIR.
+fn lower_funcs<'mir>(
+    items: &'mir [hir::Item],
+    symbol_resolver: &'mir Resolver<SymbolKind>,
+    type_tag_resolver: &'mir Resolver<CompoundTypeData>,
+) -> Vec<azhdaha_errors::Result<mir::Body<'mir>>> {
+    items
+        .par_iter()
+        .filter_map(|item| {
+            let hir::ItemKind::Func(func_def) = &item.kind else {
+                return None;
+            };
+
+            let mir_ctx = MirCtx::new(
+                symbol_resolver,
+                &func_def.label_resolver,
+                type_tag_resolver,
+                func_def.body.span,
+            );
+
+            Some(mir_ctx.lower_to_mir(func_def))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use azhdaha_errors::{Severity, Stage};
+    use compile_commands::{CompileArgs, CompileCommand, SourceFile};
+
+    use super::{CheckOptions, check_compile_db, check_source};
+
+    /// A function which fails to be lowered, since the operand of its cast is a comma
+    /// expression, along with a function which is analyzed.
+    const CODE: &str = "typedef int number;\n\nint first(int a, int b) {\n    return (number)(a, b);\n}\n\nint second(int a) {\n    return a;\n}\n";
+
+    #[test]
+    fn reports_the_items_which_fail_to_be_lowered() {
+        let options = CheckOptions {
+            path: Some("lowering.c".to_owned()),
+            ..CheckOptions::default()
+        };
+
+        let diagnostics = check_source(CODE, &options).unwrap();
+
+        let [diagnostic] = diagnostics.as_slice() else {
+            panic!("Expected a single diagnostic, found {diagnostics:?}.");
+        };
+
+        assert_eq!(diagnostic.stage, Stage::Hir);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.location.file, "lowering.c");
+        assert_eq!(diagnostic.location.line, 4);
+    }
+
+    #[test]
+    fn reports_the_leak_of_a_linear_variable() {
+        // The allocator is declared in the source code, so the test does not depend on the
+        // deployed annotated headers.
+        let code = "linear_type void *malloc(unsigned long size);\n\nint leak(void) {\n    linear_type int *ptr = malloc(4);\n    return 0;\n}\n";
+
+        let diagnostics = check_source(code, &CheckOptions::default()).unwrap();
+
+        let [diagnostic] = diagnostics.as_slice() else {
+            panic!("Expected a single diagnostic, found {diagnostics:?}.");
+        };
+
+        assert_eq!(diagnostic.stage, Stage::Analysis);
+        assert_eq!(diagnostic.code, Some("AZ0001"));
+        assert_eq!(diagnostic.variable.as_deref(), Some("ptr"));
+        assert_eq!(diagnostic.location.line, 5);
+    }
+
+    #[test]
+    fn leaves_out_the_items_of_the_headers_which_fail_to_be_lowered() {
+        let code = "#include <stddef.h>\n\nint main() {\n    return 0;\n}\n";
+
+        assert_eq!(check_source(code, &CheckOptions::default()).unwrap(), []);
+    }
+
+    #[test]
+    fn passes_the_flags_to_the_preprocessor() {
+        let code = "#ifndef ENABLED\n#error The flag is not passed.\n#endif\n";
+
+        assert!(check_source(code, &CheckOptions::default()).is_err());
+
+        let options = CheckOptions {
+            flags: vec!["-DENABLED".to_owned()],
+            ..CheckOptions::default()
+        };
+
+        assert_eq!(check_source(code, &options).unwrap(), []);
+    }
+
+    #[test]
+    fn counts_the_entries_which_fail_to_be_preprocessed() {
+        let compile_command = CompileCommand {
+            directory: env::temp_dir(),
+            file: SourceFile::File("azhdaha-missing.c".into()),
+            arguments: Some(CompileArgs::Arguments(vec![
+                "cc".to_owned(),
+                "-c".to_owned(),
+                "azhdaha-missing.c".to_owned(),
+            ])),
+            command: None,
+            output: None,
+        };
+
+        let result = check_compile_db(&vec![compile_command], &CheckOptions::default()).unwrap();
+
+        assert_eq!(result.failed_entry_count, 1);
+        assert_eq!(result.diagnostics, []);
+    }
+}
@@ -0,0 +1,698 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use analyzer::Ownership;
+use anyhow::Context;
+use ast_utils::{AstRepr, SourceInfo, SourcePosition, preprocess_path, preprocess_source};
+use azhdaha::{CheckOptions, IncrementalCheck};
+use azhdaha_errors::{Diagnostic, Location, Severity};
+use compile_commands::CompilationDatabase;
+use log::error;
+use serde_json::{Value, json};
+
+use crate::lsp::protocol::{path_from_uri, read_message, uri_from_path, write_message};
+
+/// Contains the reading and writing of the messages and the conversion of the URIs.
+mod protocol;
+
+/// The error code of the requests whose method is not supported.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The error code of the requests whose parameters are not valid.
+const INVALID_PARAMS: i64 = -32602;
+
+/// The documents are synchronized by sending their full text on each change.
+const FULL_TEXT_SYNC: u8 = 1;
+
+/// The severities of the diagnostics in the protocol.
+const ERROR_SEVERITY: u8 = 1;
+const WARNING_SEVERITY: u8 = 2;
+const INFORMATION_SEVERITY: u8 = 3;
+
+/// A document which is opened in the editor.
+struct Document {
+    path: PathBuf,
+    /// The text of the document in the editor, which may not be saved yet.
+    text: String,
+    /// The document as it was when it was analyzed the last time.
+    ast_repr: Option<AstRepr>,
+    /// The text of the document which was analyzed the last time.
+    analyzed_text: Option<String>,
+    /// The ownership of the linear variables in the functions of the document as it was
+    /// analyzed the last time, which is found on the first hover after the analysis.
+    ownership: Option<Vec<Ownership>>,
+    /// The results of analyzing the document, which are reused for its unchanged functions.
+    check: IncrementalCheck,
+}
+
+/// Analyzes the documents which are opened in the editor and reports their diagnostics,
+/// while showing the ownership of the linear variables on hover.
+pub(crate) struct Server {
+    compile_commands: Option<CompilationDatabase>,
+    options: CheckOptions,
+    documents: HashMap<String, Document>,
+}
+
+impl Server {
+    pub(crate) fn new(
+        compile_commands: Option<CompilationDatabase>,
+        options: CheckOptions,
+    ) -> Self {
+        Self {
+            compile_commands,
+            options,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Serves the client over the standard input and output until it asks the server to
+    /// exit.
+    pub(crate) fn run(&mut self) -> anyhow::Result<()> {
+        self.serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+    }
+
+    /// Serves the client over the reader and the writer until it asks the server to exit or
+    /// the input is closed.
+    fn serve(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> anyhow::Result<()> {
+        while let Some(message) = read_message(reader)? {
+            // The responses of the client to the requests of the server are not used.
+            let Some(method) = message["method"].as_str() else {
+                continue;
+            };
+
+            if method == "exit" {
+                break;
+            }
+
+            let params = &message["params"];
+
+            match message.get("id") {
+                Some(id) => {
+                    let response = match self.handle_request(method, params) {
+                        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                        Err((code, message)) => json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": { "code": code, "message": message },
+                        }),
+                    };
+
+                    write_message(writer, &response)?;
+                }
+                None => self.handle_notification(method, params, writer)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        "change": FULL_TEXT_SYNC,
+                        "save": { "includeText": true },
+                    },
+                    "hoverProvider": true,
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/hover" => self.hover(params).ok_or_else(|| {
+                (
+                    INVALID_PARAMS,
+                    "The document or the position is not valid.".to_owned(),
+                )
+            }),
+            _ => Err((
+                METHOD_NOT_FOUND,
+                format!("Method '{method}' is not supported."),
+            )),
+        }
+    }
+
+    fn handle_notification(
+        &mut self,
+        method: &str,
+        params: &Value,
+        writer: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        let Some(uri) = params["textDocument"]["uri"].as_str() else {
+            return Ok(());
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let Some(path) = path_from_uri(uri) else {
+                    error!("Failed to find the file of '{uri}'.");
+                    return Ok(());
+                };
+
+                self.documents.insert(
+                    uri.to_owned(),
+                    Document {
+                        path,
+                        text: params["textDocument"]["text"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_owned(),
+                        ast_repr: None,
+                        analyzed_text: None,
+                        ownership: None,
+                        check: IncrementalCheck::new(),
+                    },
+                );
+
+                self.publish(uri, writer)?;
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+
+                if let (Some(document), Some(text)) = (self.documents.get_mut(uri), text) {
+                    text.clone_into(&mut document.text);
+                }
+            }
+            "textDocument/didSave" => {
+                if let (Some(document), Some(text)) =
+                    (self.documents.get_mut(uri), params["text"].as_str())
+                {
+                    text.clone_into(&mut document.text);
+                }
+
+                self.publish(uri, writer)?;
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+
+                write_message(writer, &publish_diagnostics(uri, vec![]))?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Analyzes the document again and publishes its diagnostics.
+    fn publish(&mut self, uri: &str, writer: &mut impl Write) -> anyhow::Result<()> {
+        let Some(document) = self.documents.get_mut(uri) else {
+            return Ok(());
+        };
+
+        let diagnostics = document.analyze(self.compile_commands.as_ref(), &self.options);
+
+        write_message(writer, &publish_diagnostics(uri, diagnostics))?;
+
+        Ok(())
+    }
+
+    /// Shows the ownership of the linear variables which are named at the position, right
+    /// before the statement which contains it. Nothing is shown while the document has
+    /// changes which are not analyzed yet, since the position may not point at the same code.
+    fn hover(&mut self, params: &Value) -> Option<Value> {
+        let document = self
+            .documents
+            .get_mut(params["textDocument"]["uri"].as_str()?)?;
+
+        let line = usize::try_from(params["position"]["line"].as_u64()?).ok()?;
+        let character = usize::try_from(params["position"]["character"].as_u64()?).ok()?;
+
+        let (Some(ast_repr), Some(analyzed_text)) = (&document.ast_repr, &document.analyzed_text)
+        else {
+            return Some(Value::Null);
+        };
+
+        if *analyzed_text != document.text {
+            return Some(Value::Null);
+        }
+
+        let line_text = document.text.lines().nth(line)?;
+        let column = column_of(line_text, character);
+
+        let Some((name, start_column, end_column)) = access_at(line_text, column) else {
+            return Some(Value::Null);
+        };
+
+        let offset = ast_repr.source_info.offset_of(SourcePosition {
+            file: document.path.to_str()?,
+            line: line + 1,
+            column: column + 1,
+        })?;
+
+        if document.ownership.is_none() {
+            match azhdaha::ownership(ast_repr, &self.options) {
+                Ok(ownership) => document.ownership = Some(ownership),
+                Err(error) => {
+                    error!(
+                        "Failed to find the ownership in '{}' - {error:?}",
+                        document.path.display()
+                    );
+                    return Some(Value::Null);
+                }
+            }
+        }
+
+        let ownership = document
+            .ownership
+            .iter()
+            .flatten()
+            .find(|ownership| ownership.contains(offset))
+            .map(|ownership| ownership.at(offset))
+            .unwrap_or_default();
+
+        let contents: Vec<_> = ownership
+            .into_iter()
+            .filter(|(place_name, _)| names_place(&name, place_name))
+            .map(|(place_name, status)| format!("`{place_name}` {}", status.description()))
+            .collect();
+
+        if contents.is_empty() {
+            return Some(Value::Null);
+        }
+
+        Some(json!({
+            "contents": { "kind": "markdown", "value": contents.join("\n\n") },
+            "range": {
+                "start": { "line": line, "character": character_of(line_text, start_column) },
+                "end": { "line": line, "character": character_of(line_text, end_column) },
+            },
+        }))
+    }
+}
+
+impl Document {
+    /// Analyzes the text of the document in the editor by the flags of the entry of
+    /// compile-commands which applies to it, or by the default flags when there is no such
+    /// entry, and returns its diagnostics. The tree of the document is reparsed incrementally,
+    /// and only its changed functions are analyzed again.
+    fn analyze(
+        &mut self,
+        compile_commands: Option<&CompilationDatabase>,
+        options: &CheckOptions,
+    ) -> Vec<Value> {
+        let ast_repr =
+            self.preprocess(compile_commands)
+                .and_then(|source_info| match self.ast_repr.take() {
+                    Some(mut ast_repr) => {
+                        let edit = ast_repr.update(source_info)?;
+
+                        Ok((ast_repr, Some(edit)))
+                    }
+                    None => Ok((AstRepr::parse(source_info)?, None)),
+                });
+
+        let (ast_repr, edit) = match ast_repr {
+            Ok(ast_repr) => ast_repr,
+            Err(error) => {
+                self.check = IncrementalCheck::new();
+                self.analyzed_text = None;
+                self.ownership = None;
+
+                return vec![json!({
+                    "range": to_range(None, 1, 1, 1, 1),
+                    "severity": ERROR_SEVERITY,
+                    "source": env!("CARGO_PKG_NAME"),
+                    "message": format!("Failed to analyze the file - {error:#}"),
+                })];
+            }
+        };
+
+        let diagnostics = match self.check.check(&ast_repr, edit.as_ref(), options) {
+            Ok(diagnostics) => diagnostics,
+            Err(error) => {
+                error!("Failed to analyze '{}' - {error:?}", self.path.display());
+                vec![]
+            }
+        };
+
+        let path = ast_repr.source_info.path.clone();
+        self.ast_repr = Some(ast_repr);
+        self.analyzed_text = Some(self.text.clone());
+        self.ownership = None;
+
+        // The diagnostics in the headers are reported when the headers are opened.
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.location.file == path)
+            .map(|diagnostic| to_lsp_diagnostic(diagnostic, &path, &self.text))
+            .collect()
+    }
+
+    fn preprocess(
+        &self,
+        compile_commands: Option<&CompilationDatabase>,
+    ) -> anyhow::Result<SourceInfo> {
+        if let Some(source_info) = compile_commands
+            .and_then(|compile_commands| preprocess_path(compile_commands, &self.path, &self.text))
+        {
+            return source_info;
+        }
+
+        let path = self
+            .path
+            .to_str()
+            .context("UTF-8 validity for the path of the document failed.")?;
+
+        // The headers next to the document are found as they are by the compiler.
+        let directory = self.path.parent().unwrap_or(Path::new("."));
+
+        preprocess_source(
+            path,
+            &self.text,
+            &["-I".to_owned(), directory.to_string_lossy().into_owned()],
+        )
+    }
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Converts the diagnostic of the document, while its labels become the related information.
+/// The diagnostics of the items which failed to be lowered or analyzed are only informational,
+/// since the items are left out of the analysis rather than being wrong.
+fn to_lsp_diagnostic(diagnostic: &Diagnostic, path: &str, text: &str) -> Value {
+    let severity = match diagnostic.severity {
+        _ if diagnostic.stage.is_failure() => INFORMATION_SEVERITY,
+        Severity::Error => ERROR_SEVERITY,
+        Severity::Warning => WARNING_SEVERITY,
+        Severity::Note => INFORMATION_SEVERITY,
+    };
+
+    let mut message = diagnostic.message.clone();
+
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nnote: {note}"));
+    }
+
+    for help in &diagnostic.helps {
+        message.push_str(&format!("\nhelp: {help}"));
+    }
+
+    let related_information: Vec<_> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            json!({
+                "location": to_lsp_location(&label.location, path, text),
+                "message": label.message,
+            })
+        })
+        .collect();
+
+    json!({
+        "range": to_lsp_location(&diagnostic.location, path, text)["range"],
+        "severity": severity,
+        "code": diagnostic.code,
+        "source": env!("CARGO_PKG_NAME"),
+        "message": message,
+        "relatedInformation": related_information,
+    })
+}
+
+/// Converts the location, whose characters are counted in the text when it is in the
+/// document.
+fn to_lsp_location(location: &Location, path: &str, text: &str) -> Value {
+    let text = (location.file == path).then_some(text);
+
+    json!({
+        "uri": uri_from_path(Path::new(&location.file)),
+        "range": to_range(
+            text,
+            location.line,
+            location.column,
+            location.end_line,
+            location.end_column,
+        ),
+    })
+}
+
+/// Converts the range whose lines and columns start from one, where the characters are
+/// counted in UTF-16 code units as the protocol requires when the text is known.
+fn to_range(
+    text: Option<&str>,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+) -> Value {
+    let position = |line: usize, column: usize| {
+        let line = line.saturating_sub(1);
+        let column = column.saturating_sub(1);
+
+        let character = text
+            .and_then(|text| text.lines().nth(line))
+            .map_or(column, |line_text| character_of(line_text, column));
+
+        json!({ "line": line, "character": character })
+    };
+
+    json!({
+        "start": position(line, column),
+        "end": position(end_line, end_column),
+    })
+}
+
+/// Returns the character in UTF-16 code units of the column in characters, both starting
+/// from zero.
+fn character_of(line_text: &str, column: usize) -> usize {
+    line_text.chars().take(column).map(char::len_utf16).sum()
+}
+
+/// Returns the column in characters of the character in UTF-16 code units, both starting
+/// from zero.
+fn column_of(line_text: &str, character: usize) -> usize {
+    let mut length = 0;
+
+    line_text
+        .chars()
+        .take_while(|char| {
+            length += char.len_utf16();
+            length <= character
+        })
+        .count()
+}
+
+/// Returns the accesses to the fields which end with the identifier at the column, such as
+/// `node->next`, along with the columns of the identifier.
+fn access_at(line_text: &str, column: usize) -> Option<(String, usize, usize)> {
+    let chars: Vec<char> = line_text.chars().collect();
+    let is_ident = |char: char| char.is_alphanumeric() || char == '_';
+
+    if !chars.get(column).copied().is_some_and(is_ident) {
+        return None;
+    }
+
+    let mut start = column;
+    while start > 0 && is_ident(chars[start - 1]) {
+        start -= 1;
+    }
+
+    let mut end = column;
+    while end < chars.len() && is_ident(chars[end]) {
+        end += 1;
+    }
+
+    // The accessed values are included, so the field is named as the analyzer names it.
+    let mut access_start = start;
+
+    loop {
+        let operator_start = match chars[..access_start] {
+            [.., '.'] => access_start - 1,
+            [.., '-', '>'] => access_start - 2,
+            _ => break,
+        };
+
+        let mut value_start = operator_start;
+        while value_start > 0 && is_ident(chars[value_start - 1]) {
+            value_start -= 1;
+        }
+
+        if value_start == operator_start {
+            break;
+        }
+
+        access_start = value_start;
+    }
+
+    Some((chars[access_start..end].iter().collect(), start, end))
+}
+
+/// Whether the access names the linear place or one of its fields.
+fn names_place(access: &str, place_name: &str) -> bool {
+    place_name
+        .strip_prefix(access)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with("->"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use azhdaha::CheckOptions;
+    use serde_json::{Value, json};
+
+    use super::{Server, access_at, character_of, column_of, names_place, protocol};
+
+    /// Serves the messages and returns the messages which the server writes.
+    fn serve(messages: &[Value]) -> Vec<Value> {
+        let mut input = vec![];
+
+        for message in messages {
+            protocol::write_message(&mut input, message).unwrap();
+        }
+
+        let mut output = vec![];
+        Server::new(None, CheckOptions::default())
+            .serve(&mut input.as_slice(), &mut output)
+            .unwrap();
+
+        let mut reader = output.as_slice();
+        let mut responses = vec![];
+
+        while let Some(message) = protocol::read_message(&mut reader).unwrap() {
+            responses.push(message);
+        }
+
+        responses
+    }
+
+    #[test]
+    fn responds_to_the_requests() {
+        let responses = serve(&[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/definition" }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "textDocument/hover",
+                "params": {
+                    "textDocument": { "uri": "file:///main.c" },
+                    "position": { "line": 0, "character": 0 },
+                },
+            }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+            json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        ]);
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["hoverProvider"],
+            true
+        );
+        assert_eq!(responses[1]["error"]["code"], super::METHOD_NOT_FOUND);
+        assert_eq!(responses[2]["error"]["code"], super::INVALID_PARAMS);
+        assert_eq!(
+            responses[3],
+            json!({ "jsonrpc": "2.0", "id": 4, "result": null })
+        );
+    }
+
+    #[test]
+    fn clears_the_diagnostics_of_the_closed_documents() {
+        let responses = serve(&[json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didClose",
+            "params": { "textDocument": { "uri": "file:///main.c" } },
+        })]);
+
+        assert_eq!(
+            responses,
+            [json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": "file:///main.c", "diagnostics": [] },
+            })]
+        );
+    }
+
+    #[test]
+    fn publishes_the_diagnostics_of_the_opened_and_the_saved_documents() {
+        let uri = protocol::uri_from_path(&env::temp_dir().join("azhdaha-lsp-main.c"));
+        let text = "// azhdaha-allow(leak): nothing leaks.\nint main() {\n    return 0;\n}\n";
+
+        let responses = serve(&[
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": { "textDocument": { "uri": uri, "text": text } },
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didSave",
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "text": "int main() {\n    return 0;\n}\n",
+                },
+            }),
+        ]);
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["params"]["uri"], uri);
+        assert_eq!(
+            responses[0]["params"]["diagnostics"][0]["severity"],
+            super::WARNING_SEVERITY
+        );
+        assert_eq!(
+            responses[0]["params"]["diagnostics"][0]["range"]["start"],
+            json!({ "line": 0, "character": 0 })
+        );
+        // The saved text is analyzed rather than the file on the disk.
+        assert_eq!(responses[1]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn converts_between_the_columns_and_the_utf16_characters() {
+        let line_text = "a𝔸é b";
+
+        assert_eq!(character_of(line_text, 2), 3);
+        assert_eq!(character_of(line_text, 4), 5);
+        assert_eq!(column_of(line_text, 3), 2);
+        assert_eq!(column_of(line_text, 5), 4);
+        // The character in the middle of a surrogate pair is in the column of its character.
+        assert_eq!(column_of(line_text, 2), 1);
+    }
+
+    #[test]
+    fn finds_the_accesses_at_the_columns() {
+        let line_text = "free(list->head.next);";
+
+        assert_eq!(access_at(line_text, 5), Some(("list".to_owned(), 5, 9)));
+        assert_eq!(
+            access_at(line_text, 12),
+            Some(("list->head".to_owned(), 11, 15))
+        );
+        assert_eq!(
+            access_at(line_text, 17),
+            Some(("list->head.next".to_owned(), 16, 20))
+        );
+        assert_eq!(access_at(line_text, 4), None);
+    }
+
+    #[test]
+    fn matches_the_accesses_with_the_places() {
+        assert!(names_place("list", "list"));
+        assert!(names_place("list", "list->head"));
+        assert!(names_place("list->head", "list->head.next"));
+        assert!(!names_place("list", "list2"));
+        assert!(!names_place("list->head", "list"));
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use serde_json::Value;
+
+/// The header which carries the length of the content of each message.
+const CONTENT_LENGTH_HEADER: &str = "Content-Length";
+
+/// The scheme of the URIs which refer to the local files.
+const FILE_SCHEME: &str = "file://";
+
+/// Reads the next message of the client, which is [`None`] once the input is closed.
+pub(crate) fn read_message(reader: &mut impl BufRead) -> anyhow::Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if reader
+            .read_line(&mut line)
+            .context("Failed to read the header of a message.")?
+            == 0
+        {
+            return Ok(None);
+        }
+
+        // The headers are terminated by an empty line.
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case(CONTENT_LENGTH_HEADER)
+        {
+            content_length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .context("Failed to parse the length of a message.")?,
+            );
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        bail!("The message has no '{CONTENT_LENGTH_HEADER}' header.");
+    };
+
+    let mut content = vec![0; content_length];
+
+    reader
+        .read_exact(&mut content)
+        .context("Failed to read the content of a message.")?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .context("Failed to parse the content of a message.")
+}
+
+/// Writes the message to the client.
+pub(crate) fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = serde_json::to_vec(message)?;
+
+    write!(writer, "{CONTENT_LENGTH_HEADER}: {}\r\n\r\n", content.len())?;
+    writer.write_all(&content)?;
+    writer.flush()
+}
+
+/// Returns the path of the local file which the URI refers to.
+pub(crate) fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix(FILE_SCHEME)?.as_bytes();
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut index = 0;
+
+    while index < path.len() {
+        match path[index] {
+            b'%' => {
+                let hex = str::from_utf8(path.get(index + 1..index + 3)?).ok()?;
+
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// Returns the URI which refers to the local file, where the relative paths are resolved
+/// against the current directory.
+pub(crate) fn uri_from_path(path: &Path) -> String {
+    let path = match env::current_dir() {
+        Ok(current_dir) if path.is_relative() => current_dir.join(path),
+        _ => path.to_owned(),
+    };
+
+    // The components such as `.` are left out.
+    let path: PathBuf = path.components().collect();
+
+    let mut uri = FILE_SCHEME.to_owned();
+
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::{path_from_uri, read_message, uri_from_path, write_message};
+
+    #[test]
+    fn reads_the_written_messages() {
+        let first = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" });
+        let second = json!({ "jsonrpc": "2.0", "method": "exit", "params": "é" });
+
+        let mut buffer = vec![];
+        write_message(&mut buffer, &first).unwrap();
+        write_message(&mut buffer, &second).unwrap();
+
+        let mut reader = buffer.as_slice();
+
+        assert_eq!(read_message(&mut reader).unwrap(), Some(first));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(second));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn reads_the_headers_case_insensitively() {
+        let mut reader: &[u8] =
+            b"content-length: 2\r\nContent-Type: application/vscode-jsonrpc\r\n\r\n{}";
+
+        assert_eq!(read_message(&mut reader).unwrap(), Some(json!({})));
+    }
+
+    #[test]
+    fn rejects_the_messages_without_a_length() {
+        let mut reader: &[u8] = b"Content-Type: application/vscode-jsonrpc\r\n\r\n{}";
+
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn rejects_the_truncated_messages() {
+        let mut reader: &[u8] = b"Content-Length: 10\r\n\r\n{}";
+
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn converts_between_the_paths_and_the_uris() {
+        let path = Path::new("/project/src/my file+é.c");
+        let uri = uri_from_path(path);
+
+        assert_eq!(uri, "file:///project/src/my%20file%2B%C3%A9.c");
+        assert_eq!(path_from_uri(&uri).as_deref(), Some(path));
+        assert_eq!(
+            uri_from_path(Path::new("/project/./src/main.c")),
+            "file:///project/src/main.c"
+        );
+    }
+
+    #[test]
+    fn rejects_the_uris_which_are_not_local_files() {
+        assert_eq!(path_from_uri("untitled:Untitled-1"), None);
+        assert_eq!(path_from_uri("file:///project/%2"), None);
+        assert_eq!(path_from_uri("file:///project/%FF"), None);
+    }
+}
@@ -2,28 +2,118 @@
 //! in order to detect memory leakage by applying linear type system principles.
 //!
 
-use std::io::Write;
+use std::{num::NonZeroUsize, process::ExitCode, time::Duration};
 
-use analyzer::LinearCtx;
+use analyzer::ViolationKind;
+use anyhow::Context;
 use ast_utils::AstRepr;
+use azhdaha::{Cache, CheckOptions};
+use azhdaha_errors::{Diagnostic, HumanRenderer, JsonRenderer, Render, SarifRenderer, Severity};
+use cli_utils::{Args, Command, FailOn, Format};
 use log::error;
 
 use env_logger::Env;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use repr::{
-    hir::{self, HirCtx},
-    mir::MirCtx,
-};
 
-#[allow(clippy::print_stdout)]
-fn main() -> anyhow::Result<()> {
-    env_logger::Builder::from_env(Env::default().default_filter_or("warn"))
-        .format_source_path(true)
-        .init();
+use crate::baseline::{Baseline, Fingerprint};
+
+/// Contains the baseline of the findings which are accepted when adopting the checker.
+mod baseline;
+/// Contains the language server which reports the diagnostics to the editors.
+mod lsp;
+/// Contains the watching of the files which analyzes the affected entries again on changes.
+mod watch;
+
+/// The messages which are logged by default.
+const DEFAULT_LOG_FILTER: &str = "warn";
+
+/// The messages which are logged with `--verbose`, which include the items that are left out
+/// of the analysis.
+const VERBOSE_LOG_FILTER: &str = "warn,azhdaha=info";
 
+/// The exit code when violations of the linear rules are found.
+const VIOLATIONS_EXIT_CODE: u8 = 1;
+
+/// The exit code when the tool fails to preprocess, lower or analyze the entries.
+const TOOL_FAILURE_EXIT_CODE: u8 = 2;
+
+#[allow(clippy::print_stderr)]
+fn main() -> ExitCode {
     let args = cli_utils::parse_args();
 
-    let ast_reprs = AstRepr::construct(&args.compile_commands)?;
+    env_logger::Builder::from_env(Env::default().default_filter_or(if args.verbose {
+        VERBOSE_LOG_FILTER
+    } else {
+        DEFAULT_LOG_FILTER
+    }))
+    .format_source_path(true)
+    .init();
+
+    match run(&args) {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+
+            ExitCode::from(TOOL_FAILURE_EXIT_CODE)
+        }
+    }
+}
+
+/// Runs the command and returns the code which the process should exit with.
+#[allow(clippy::print_stdout)]
+fn run(args: &Args) -> anyhow::Result<ExitCode> {
+    if let Some(Command::Explain { code }) = &args.command {
+        let kind = ViolationKind::from_code(code)
+            .with_context(|| format!("Unknown diagnostic code '{code}'."))?;
+
+        println!("{}", kind.explanation());
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Lsp {
+        compile_commands,
+        allow_exit_leaks,
+    }) = &args.command
+    {
+        let options = CheckOptions {
+            allow_exit_leaks: *allow_exit_leaks,
+            ..CheckOptions::default()
+        };
+
+        lsp::Server::new(compile_commands.clone(), options).run()?;
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Watch {
+        compile_commands,
+        allow_exit_leaks,
+        jobs,
+        interval,
+    }) = &args.command
+    {
+        build_thread_pool(*jobs)?;
+
+        let options = CheckOptions {
+            allow_exit_leaks: *allow_exit_leaks,
+            ..CheckOptions::default()
+        };
+
+        watch::Watcher::new(
+            compile_commands.clone(),
+            options,
+            Duration::from_millis(*interval),
+        )
+        .run();
+    }
+
+    let Some(compile_commands) = &args.compile_commands else {
+        unreachable!("compile-commands are required without a command");
+    };
+
+    build_thread_pool(args.jobs)?;
+
+    let (ast_reprs, error_count) = AstRepr::construct(compile_commands)?;
 
     if args.dot_graph {
         for (index, ast_repr) in ast_reprs.iter().enumerate() {
@@ -35,64 +125,155 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    for ast_repr in ast_reprs {
-        let hir_ctx = HirCtx::new(&ast_repr);
-        let (items, symbol_resolver, type_tag_resolver, _) = hir_ctx.lower_to_hir();
+    let renderer: Box<dyn Render> = match args.format {
+        Format::Human => Box::new(HumanRenderer::new(std::io::stdout())),
+        Format::Json => Box::new(JsonRenderer::new(std::io::stdout())),
+        Format::Sarif => Box::new(SarifRenderer::new(
+            std::io::stdout(),
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            ViolationKind::ALL.map(ViolationKind::rule).to_vec(),
+        )),
+    };
 
-        let linear_ctx = LinearCtx::new(&ast_repr.source_info.path, &ast_repr.source_info.code)?;
+    let mut findings = Findings {
+        baseline: args.baseline.as_deref().map(Baseline::read).transpose()?,
+        fingerprints: vec![],
+        renderer,
+        violation_count: 0,
+        tool_failure_count: error_count as usize,
+    };
 
-        let analyze = |item: hir::Item| {
-            let hir::ItemKind::Func(func_def) = item.kind else {
-                return None;
-            };
+    let options = CheckOptions {
+        allow_exit_leaks: args.allow_exit_leaks,
+        whole_program: args.whole_program,
+        ..CheckOptions::default()
+    };
 
-            let mir_ctx = MirCtx::new(
-                &symbol_resolver,
-                &func_def.label_resolver,
-                &type_tag_resolver,
-                func_def.body.span,
-            );
+    // The entries are lowered again, so the MIR bodies are printed in the order of the entries
+    // rather than the order which the threads analyze them in.
+    if args.show_mir {
+        for ast_repr in &ast_reprs {
+            azhdaha::inspect_mir(ast_repr, |mir_body| println!("{mir_body}"));
+        }
+    }
 
-            let mir_body = mir_ctx.lower_to_mir(&func_def);
+    // The entries which are analyzed together depend on each other, so their results are
+    // not cached.
+    let diagnostics = if (args.cache || args.cache_dir.is_some()) && !args.whole_program {
+        Cache::open(args.cache_dir.clone(), &options)?.check_entries(&ast_reprs, &options)?
+    } else {
+        azhdaha::check_entries(&ast_reprs, &options)?
+    };
 
-            match mir_body {
-                Ok(mir_body) => {
-                    if args.show_mir {
-                        println!("{mir_body}");
-                    }
+    report_entries(args, &ast_reprs, diagnostics, &mut findings)?;
 
-                    linear_ctx.analyze(&mir_body)
-                }
-                Err(error) => {
-                    error!("Failed to construct MIR - {error:?}");
+    if let Some(path) = &args.write_baseline {
+        baseline::write(path, &findings.fingerprints)?;
+    }
+
+    findings.renderer.finish()?;
+
+    if findings.tool_failure_count > 0 && args.fail_on.contains(&FailOn::ToolFailures) {
+        return Ok(ExitCode::from(TOOL_FAILURE_EXIT_CODE));
+    }
+
+    if findings.violation_count > 0 && args.fail_on.contains(&FailOn::Violations) {
+        return Ok(ExitCode::from(VIOLATIONS_EXIT_CODE));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Limits the number of the threads which preprocess, parse and analyze the entries, which
+/// defaults to the number of the available CPUs.
+fn build_thread_pool(jobs: Option<NonZeroUsize>) -> anyhow::Result<()> {
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs.get())
+            .build_global()
+            .context("Failed to create the threads of the jobs.")?;
+    }
+
+    Ok(())
+}
+
+/// The findings which are gathered while analyzing the entries.
+struct Findings {
+    /// The findings which should not be reported.
+    baseline: Option<Baseline>,
+    /// The fingerprints of all the findings, which are written as a new baseline.
+    fingerprints: Vec<Fingerprint>,
+    /// The format which the diagnostics are written in.
+    renderer: Box<dyn Render>,
+    /// The number of the reported diagnostics whose severity is error.
+    violation_count: usize,
+    /// The number of the entries and the items which failed to be preprocessed or lowered.
+    tool_failure_count: usize,
+}
 
-                    None
-                }
+/// Reports the diagnostics of the entries, except the findings which are accepted by the
+/// baseline.
+#[allow(clippy::print_stdout)]
+fn report_entries(
+    args: &Args,
+    ast_reprs: &[AstRepr],
+    diagnostics: Vec<Vec<Diagnostic>>,
+    findings: &mut Findings,
+) -> anyhow::Result<()> {
+    for (ast_repr, mut diagnostics) in ast_reprs.iter().zip(diagnostics) {
+        diagnostics.retain(|diagnostic| {
+            if diagnostic.stage.is_failure() {
+                findings.tool_failure_count += 1;
+
+                return true;
             }
-        };
 
-        let reports: Vec<Vec<_>> = if args.show_mir {
-            items.into_iter().filter_map(analyze).collect()
-        } else {
-            items.into_par_iter().filter_map(analyze).collect()
-        };
+            let fingerprint = Fingerprint::new(diagnostic);
 
-        if reports.is_empty() {
-            println!(
-                "Entry \"{}\" was analyzed successfully.",
-                ast_repr.source_info.path
-            );
-        } else {
-            println!(
-                "Entry \"{}\" was found to be problematic.",
-                ast_repr.source_info.path
-            );
-        }
+            let is_accepted = findings
+                .baseline
+                .as_mut()
+                .is_some_and(|baseline| baseline.accepts(&fingerprint));
+
+            findings.fingerprints.push(fingerprint);
 
-        if !args.do_not_report {
-            for report in reports {
-                std::io::stdout().write_all(&report)?;
+            // The warnings, such as the unused suppressions, are reported without failing the
+            // run.
+            if !is_accepted && diagnostic.severity == Severity::Error {
+                findings.violation_count += 1;
             }
+
+            !is_accepted
+        });
+
+        if args.format == Format::Human {
+            let failure_count = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.stage.is_failure())
+                .count();
+
+            let path = &ast_repr.source_info.path;
+
+            if failure_count < diagnostics.len() {
+                println!("Entry \"{path}\" was found to be problematic.");
+            } else if failure_count > 0 {
+                println!(
+                    "Entry \"{path}\" was partially analyzed, {failure_count} items failed to be lowered or analyzed."
+                );
+            } else {
+                println!("Entry \"{path}\" was analyzed successfully.");
+            }
+
+            if args.do_not_report {
+                continue;
+            }
+        }
+
+        for diagnostic in &diagnostics {
+            findings
+                .renderer
+                .render(diagnostic, &ast_repr.source_info)?;
         }
     }
 
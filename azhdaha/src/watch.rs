@@ -0,0 +1,357 @@
+#![allow(clippy::missing_docs_in_private_items)]
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, mem,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use ast_utils::AstRepr;
+use azhdaha::CheckOptions;
+use azhdaha_errors::Diagnostic;
+use compile_commands::{CompilationDatabase, CompileCommand, SourceFile};
+use log::error;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::baseline::{Baseline, Fingerprint};
+
+/// The modification times of the files, which are `None` when the files cannot be read.
+type ModifiedTimes = HashMap<PathBuf, Option<SystemTime>>;
+
+/// The results of analyzing an entry of compile-commands the last time.
+#[derive(Default)]
+struct Entry {
+    /// The findings of the entry, without the diagnostics of the items which failed to be
+    /// lowered.
+    findings: Vec<Diagnostic>,
+    /// The files which the entry depends on, along with their modification times when the
+    /// entry was analyzed.
+    files: ModifiedTimes,
+    /// Whether the entry failed to be preprocessed, parsed or analyzed.
+    failed: bool,
+}
+
+/// Analyzes the entries of compile-commands again whenever the files which they depend on
+/// change, and prints the findings which are new or resolved since the last time.
+pub(crate) struct Watcher {
+    compile_commands: CompilationDatabase,
+    options: CheckOptions,
+    interval: Duration,
+    entries: Vec<Entry>,
+}
+
+impl Watcher {
+    pub(crate) fn new(
+        compile_commands: CompilationDatabase,
+        options: CheckOptions,
+        interval: Duration,
+    ) -> Self {
+        let entries = compile_commands.iter().map(|_| Entry::default()).collect();
+
+        Self {
+            compile_commands,
+            options,
+            interval,
+            entries,
+        }
+    }
+
+    /// Analyzes all of the entries, then checks the files for changes until the process is
+    /// interrupted. The entries which fail to be analyzed are reported and analyzed again once
+    /// their files change.
+    #[allow(clippy::print_stdout)]
+    pub(crate) fn run(&mut self) -> ! {
+        let indices: Vec<_> = (0..self.entries.len()).collect();
+
+        let (new_count, _) = self.check(&indices, &ModifiedTimes::new());
+
+        println!(
+            "Found {new_count} findings in {} entries. Watching {} files for changes.",
+            self.entries.len(),
+            self.modified_times().len()
+        );
+
+        loop {
+            thread::sleep(self.interval);
+
+            let modified_times = self.modified_times();
+
+            let changed_files: BTreeSet<&Path> = self
+                .entries
+                .iter()
+                .flat_map(|entry| &entry.files)
+                .filter(|(file, modified_time)| modified_times[*file] != **modified_time)
+                .map(|(file, _)| file.as_path())
+                .collect();
+
+            if changed_files.is_empty() {
+                continue;
+            }
+
+            let affected_indices: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| {
+                    entry
+                        .files
+                        .keys()
+                        .any(|file| changed_files.contains(file.as_path()))
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            println!(
+                "Changed {}, analyzing {} entries again.",
+                changed_files
+                    .iter()
+                    .map(|file| format!("'{}'", file.display()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                affected_indices.len()
+            );
+
+            let (new_count, resolved_count) = self.check(&affected_indices, &modified_times);
+
+            println!("{new_count} new and {resolved_count} resolved findings.");
+        }
+    }
+
+    /// Analyzes the entries again and prints the findings which are new or resolved, along
+    /// with the number of each of them. The files which were checked for changes before
+    /// analyzing the entries keep their modification times, so the changes which are made
+    /// while the entries are analyzed are found by the next check.
+    #[allow(clippy::print_stdout)]
+    fn check(&mut self, indices: &[usize], modified_times: &ModifiedTimes) -> (usize, usize) {
+        // The entries are analyzed in parallel, but their results are printed in the order of
+        // the entries.
+        let results: Vec<_> = indices
+            .par_iter()
+            .map(|&index| {
+                let result =
+                    check_entry(&self.compile_commands[index], &self.options, modified_times);
+
+                (index, result)
+            })
+            .collect();
+
+        let mut new_count = 0;
+        let mut resolved_count = 0;
+
+        for (index, result) in results {
+            let entry = match result {
+                Ok(entry) => entry,
+                // The entry may fail to be analyzed while its files are being written, so it
+                // keeps its findings and is analyzed again once its files change.
+                Err(error) => {
+                    error!("Failed to analyze an entry of compile-commands - {error:?}");
+
+                    let old_entry = &mut self.entries[index];
+                    let files = old_entry.files.keys().cloned().collect();
+
+                    old_entry.files =
+                        watched_files(&self.compile_commands[index], files, modified_times);
+                    old_entry.failed = true;
+
+                    continue;
+                }
+            };
+
+            let old_entry = mem::replace(&mut self.entries[index], entry);
+            let (new_findings, resolved_findings) =
+                diff(&old_entry.findings, &self.entries[index].findings);
+
+            for finding in &new_findings {
+                println!("+ {}", summarize(finding));
+            }
+
+            for finding in &resolved_findings {
+                println!("- {}", summarize(finding));
+            }
+
+            new_count += new_findings.len();
+            resolved_count += resolved_findings.len();
+        }
+
+        let failed_count = self.entries.iter().filter(|entry| entry.failed).count();
+
+        if failed_count > 0 {
+            println!("{failed_count} entries failed to be preprocessed, parsed or analyzed.");
+        }
+
+        (new_count, resolved_count)
+    }
+
+    /// Returns the current modification times of the files which the entries depend on.
+    fn modified_times(&self) -> ModifiedTimes {
+        let mut modified_times = ModifiedTimes::new();
+
+        for file in self.entries.iter().flat_map(|entry| entry.files.keys()) {
+            if !modified_times.contains_key(file) {
+                modified_times.insert(file.clone(), modified_time(file));
+            }
+        }
+
+        modified_times
+    }
+}
+
+/// Preprocesses and analyzes the entry, while recording the modification times of the files
+/// which it depends on.
+fn check_entry(
+    compile_command: &CompileCommand,
+    options: &CheckOptions,
+    modified_times: &ModifiedTimes,
+) -> anyhow::Result<Entry> {
+    let (ast_reprs, failed_count) = AstRepr::construct(&vec![compile_command.clone()])?;
+
+    let findings = azhdaha::check_entries(&ast_reprs, options)?
+        .into_iter()
+        .flatten()
+        .filter(|diagnostic| !diagnostic.stage.is_failure())
+        .collect();
+
+    let files = ast_reprs
+        .iter()
+        .flat_map(|ast_repr| ast_repr.source_info.files())
+        .collect();
+
+    Ok(Entry {
+        findings,
+        files: watched_files(compile_command, files, modified_times),
+        failed: failed_count > 0,
+    })
+}
+
+/// Returns the files which the entry depends on along with their modification times, which
+/// are the given times of the files that were checked for changes before analyzing the entry.
+fn watched_files(
+    compile_command: &CompileCommand,
+    mut files: Vec<PathBuf>,
+    modified_times: &ModifiedTimes,
+) -> ModifiedTimes {
+    // The file of the entry is watched even when it fails to be preprocessed, and the
+    // directory of an entry which is not specific to a file is watched for the files which
+    // are added to it or removed from it.
+    files.push(match &compile_command.file {
+        SourceFile::File(file) => compile_command.directory.join(file),
+        SourceFile::All => compile_command.directory.clone(),
+    });
+
+    files
+        .into_iter()
+        .map(|file| {
+            let modified_time = modified_times
+                .get(&file)
+                .copied()
+                .unwrap_or_else(|| modified_time(&file));
+
+            (file, modified_time)
+        })
+        .collect()
+}
+
+/// Returns the findings which are only found by the new analysis and the findings which are
+/// only found by the old one. The findings are matched by their fingerprints, so the findings
+/// which are only moved to other lines are neither new nor resolved.
+fn diff<'a>(
+    old_findings: &'a [Diagnostic],
+    new_findings: &'a [Diagnostic],
+) -> (Vec<&'a Diagnostic>, Vec<&'a Diagnostic>) {
+    let mut old_baseline: Baseline = old_findings.iter().map(Fingerprint::new).collect();
+    let mut new_baseline: Baseline = new_findings.iter().map(Fingerprint::new).collect();
+
+    (
+        new_findings
+            .iter()
+            .filter(|finding| !old_baseline.accepts(&Fingerprint::new(finding)))
+            .collect(),
+        old_findings
+            .iter()
+            .filter(|finding| !new_baseline.accepts(&Fingerprint::new(finding)))
+            .collect(),
+    )
+}
+
+/// Returns the location, the code and the message of the finding on a single line.
+fn summarize(finding: &Diagnostic) -> String {
+    let location = &finding.location;
+
+    let code = finding
+        .code
+        .map(|code| format!("[{code}]"))
+        .unwrap_or_default();
+
+    format!(
+        "{}:{}:{}: {}{code}: {}",
+        location.file,
+        location.line,
+        location.column,
+        finding.severity.name(),
+        finding.message
+    )
+}
+
+fn modified_time(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, summarize};
+    use crate::baseline::tests::diagnostic;
+
+    #[test]
+    fn reports_the_new_and_the_resolved_findings() {
+        let old_findings = [
+            diagnostic("main", 3, "free(ptr);"),
+            diagnostic("main", 5, "use(ptr);"),
+        ];
+        let new_findings = [
+            diagnostic("main", 5, "use(ptr);"),
+            diagnostic("helper", 9, "free(ptr);"),
+        ];
+
+        let (new, resolved) = diff(&old_findings, &new_findings);
+
+        assert_eq!(new, [&new_findings[1]]);
+        assert_eq!(resolved, [&old_findings[0]]);
+    }
+
+    #[test]
+    fn ignores_the_findings_which_are_only_moved() {
+        let old_findings = [diagnostic("main", 3, "free(ptr);")];
+        let new_findings = [diagnostic("main", 8, "free(ptr);")];
+
+        let (new, resolved) = diff(&old_findings, &new_findings);
+
+        assert!(new.is_empty());
+        assert!(resolved.is_empty());
+    }
+
+    #[test]
+    fn counts_the_repeated_findings() {
+        let old_findings = [diagnostic("main", 3, "free(ptr);")];
+        let new_findings = [
+            diagnostic("main", 3, "free(ptr);"),
+            diagnostic("main", 4, "free(ptr);"),
+        ];
+
+        let (new, resolved) = diff(&old_findings, &new_findings);
+
+        assert_eq!(new.len(), 1);
+        assert!(resolved.is_empty());
+    }
+
+    #[test]
+    fn summarizes_a_finding_on_a_single_line() {
+        assert_eq!(
+            summarize(&diagnostic("main", 3, "free(ptr);")),
+            "main.c:3:5: error[AZ0001]: The memory is leaked."
+        );
+    }
+}
@@ -1,16 +1,26 @@
-use std::fs;
+use std::{fs, num::NonZeroUsize, path::PathBuf};
 
 use anyhow::Context;
-use clap::{Parser, ValueHint};
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 use compile_commands::CompilationDatabase;
 
 /// A cli to interact with azhdaha.
 #[derive(Parser, Debug)]
-#[command(version)]
+#[command(
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     /// Path to compile-commands which must be formatted as json.
-    #[arg(value_parser = parse_compile_commands, value_hint = ValueHint::FilePath)]
-    pub compile_commands: CompilationDatabase,
+    #[arg(
+        required = true,
+        value_parser = parse_compile_commands,
+        value_hint = ValueHint::FilePath
+    )]
+    pub compile_commands: Option<CompilationDatabase>,
     /// Determines whether the source code should be annotated before analyzing or not.
     #[arg(long)]
     pub annotate: bool,
@@ -20,9 +30,119 @@ pub struct Args {
     /// Determines whether the reports should be printed or not.
     #[arg(long)]
     pub do_not_report: bool,
+    /// Determines whether the items of the included headers which failed to be lowered should
+    /// be logged or not. They are left out of the analysis without being reported otherwise.
+    #[arg(long)]
+    pub verbose: bool,
     /// Determines whether the generated MIRs should be printed or not.
     #[arg(long)]
     pub show_mir: bool,
+    /// Determines whether the memory leakages on the paths which exit the program by calling
+    /// a noreturn function should be accepted or not.
+    #[arg(long)]
+    pub allow_exit_leaks: bool,
+    /// Determines whether all the entries of compile-commands should be analyzed together as
+    /// one program, sharing the definitions of their functions, or not.
+    #[arg(long)]
+    pub whole_program: bool,
+    /// Determines the format which the results are printed in.
+    #[arg(long, value_enum, default_value_t = Format::Human)]
+    pub format: Format,
+    /// Path to a file which the current findings should be recorded in as a baseline.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub write_baseline: Option<PathBuf>,
+    /// Path to a baseline whose recorded findings should not be reported.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub baseline: Option<PathBuf>,
+    /// Determines whether the results of the entries should be cached, so the unchanged
+    /// entries and functions are not analyzed again by the next runs or not. The results are
+    /// not cached with `--whole-program`.
+    #[arg(long)]
+    pub cache: bool,
+    /// Path to the directory which the results are cached in instead of the data directory
+    /// of azhdaha, which implies `--cache`.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
+    /// The number of the entries which are preprocessed, parsed and analyzed at the same time,
+    /// which defaults to the number of the available CPUs. The results are printed in the
+    /// order of the entries regardless of it.
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+    /// Determines which outcomes of the analysis should make the process exit with a
+    /// non-zero code.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [FailOn::Violations, FailOn::ToolFailures]
+    )]
+    pub fail_on: Vec<FailOn>,
+}
+
+/// The commands which do not analyze compile-commands at once.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Prints the detailed explanation of a diagnostic code, such as AZ0001.
+    Explain {
+        /// The code of the diagnostic.
+        code: String,
+    },
+    /// Runs a language server over the standard input and output, which analyzes the files
+    /// when they are opened or saved.
+    Lsp {
+        /// Path to compile-commands which the files are preprocessed by, which must be
+        /// formatted as json.
+        #[arg(
+            long,
+            value_parser = parse_compile_commands,
+            value_hint = ValueHint::FilePath
+        )]
+        compile_commands: Option<CompilationDatabase>,
+        /// Determines whether the memory leakages on the paths which exit the program by
+        /// calling a noreturn function should be accepted or not.
+        #[arg(long)]
+        allow_exit_leaks: bool,
+    },
+    /// Watches the source files and the headers of the entries of compile-commands, and
+    /// analyzes the affected entries again whenever one of them changes while printing the
+    /// findings which are new or resolved.
+    Watch {
+        /// Path to compile-commands which must be formatted as json.
+        #[arg(value_parser = parse_compile_commands, value_hint = ValueHint::FilePath)]
+        compile_commands: CompilationDatabase,
+        /// Determines whether the memory leakages on the paths which exit the program by
+        /// calling a noreturn function should be accepted or not.
+        #[arg(long)]
+        allow_exit_leaks: bool,
+        /// The number of the entries which are preprocessed, parsed and analyzed at the same
+        /// time, which defaults to the number of the available CPUs.
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+        /// The number of milliseconds between the checks of the files for changes.
+        #[arg(long, value_name = "MS", default_value_t = 500)]
+        interval: u64,
+    },
+}
+
+/// The formats which the results of the analysis can be printed in.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable reports along with the status of each entry.
+    Human,
+    /// One JSON record per line for each diagnostic.
+    Json,
+    /// A SARIF 2.1.0 log containing all of the diagnostics.
+    Sarif,
+}
+
+/// The outcomes of the analysis which can fail the build.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailOn {
+    /// Violations of the linear rules, other than the warnings, are found, which exits with
+    /// code 1.
+    Violations,
+    /// Entries fail to be preprocessed or lowered, which exits with code 2.
+    ToolFailures,
 }
 
 /// Parse compile-commands into a [`CompilationDatabase`].
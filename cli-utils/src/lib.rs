@@ -3,4 +3,4 @@
 /// The module responsible for parsing the cli's commands.
 mod arg_parser;
 
-pub use arg_parser::parse_args;
+pub use arg_parser::{Args, Command, FailOn, Format, parse_args};
@@ -0,0 +1,16 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+#include "buffer.h"
+
+LINEAR_TYPE char *buffer_new(size_t size) {
+    LINEAR_TYPE char *buffer = malloc(size);
+
+    return buffer;
+}
+
+void buffer_free(LINEAR_TYPE char *buffer) {
+    free(buffer);
+
+    return;
+}
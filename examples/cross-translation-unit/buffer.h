@@ -0,0 +1,8 @@
+#include <stddef.h>
+
+// The declarations are not annotated while the definitions in 'buffer.c' are,
+// so the ownership of the buffers is only visible when the whole program is
+// analyzed with '--whole-program'.
+char *buffer_new(size_t size);
+
+void buffer_free(char *buffer);
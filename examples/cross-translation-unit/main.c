@@ -0,0 +1,15 @@
+#include <azhdaha.h>
+
+#include "buffer.h"
+
+// Variable 'buffer' is freed by 'buffer_free' which is defined in another
+// translation unit and then it is accessed.
+int main() {
+    LINEAR_TYPE char *buffer = buffer_new(100);
+
+    buffer_free(buffer);
+
+    buffer[0] = 'a';
+
+    return 0;
+}
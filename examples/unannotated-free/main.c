@@ -0,0 +1,21 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+// Function 'release' is not annotated, but it frees its parameter, so the ownership
+// of the value of 'ptr' is moved to it and 'ptr' is accessed after being freed
+// in 'main' function.
+void release(int *ptr) {
+    free(ptr);
+
+    return;
+}
+
+int main() {
+    LINEAR_TYPE int *ptr = malloc(100);
+
+    release(ptr);
+
+    *ptr = 1;
+
+    return 0;
+}
\ No newline at end of file
@@ -83,6 +83,7 @@ impl MirDisplay for Terminator {
                 result
             }
             TerminatorKind::Return => "return;".to_owned(),
+            TerminatorKind::Unreachable => "unreachable;".to_owned(),
         }
     }
 }
@@ -244,6 +245,10 @@ impl MirDisplay for Ty {
             result.push_str("linear ");
         }
 
+        if self.is_borrowed {
+            result.push_str("borrowed ");
+        }
+
         for qual in &self.quals {
             result.push_str(&format!("{} ", qual.mir_display(body)));
         }
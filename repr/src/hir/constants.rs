@@ -114,6 +114,9 @@ pub(crate) const SEMICOLON: &str = ";";
 
 /// An identifiers used for [`Ty`]'s construction determining whether the type is linear or not.
 pub(crate) const LINEAR_TY_SPECIFIER: &str = "linear_type_specifier";
+/// An identifiers used for [`Ty`]'s construction determining whether the type is borrowed or
+/// not, which is the name of the GNU attribute that `LINEAR_BORROW` expands to.
+pub(crate) const BORROWED_ATTRIBUTE: &str = "borrowed_type";
 
 /// An identifiers used for [`TyQual`]'s construction representing a [`TyQual`].
 pub(crate) const TYPE_QUALIFIER: &str = "type_qualifier";
@@ -131,6 +134,20 @@ pub(crate) const ATOMIC: &str = "_Atomic";
 pub(crate) const EXTENSION: &str = "__extension__";
 /// An identifiers used for [`TyQual`]'s construction representing a [`TyQual::NoReturn`].
 pub(crate) const NORETURN: &str = "noreturn";
+/// An identifiers used for [`TyQual`]'s construction representing a [`TyQual::NoReturn`].
+pub(crate) const C11_NORETURN: &str = "_Noreturn";
+/// An identifiers used for [`TyQual`]'s construction representing a [`TyQual::NoReturn`] in
+/// the GNU attributes.
+pub(crate) const GNU_NORETURN: &str = "__noreturn__";
+/// An identifiers used for [`TyQual`]'s construction representing a GNU attribute which may
+/// determine a [`TyQual::NoReturn`].
+pub(crate) const ATTRIBUTE_SPECIFIER: &str = "attribute_specifier";
+/// An identifiers used for [`TyQual`]'s construction representing a standard attribute which
+/// may determine a [`TyQual::NoReturn`].
+pub(crate) const ATTRIBUTE_DECLARATION: &str = "attribute_declaration";
+/// An identifiers used for [`TyQual`]'s construction representing the arguments of a GNU
+/// attribute.
+pub(crate) const ARGUMENT_LIST: &str = "argument_list";
 
 /// An identifiers used for [`Storage`]'s construction.
 pub(crate) const STORAGE_CLASS_SPECIFIER: &str = "storage_class_specifier";
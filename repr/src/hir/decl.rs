@@ -31,6 +31,16 @@ pub struct FuncSig {
     pub span: Span,
 }
 
+impl FuncSig {
+    /// Whether the function is declared as `_Noreturn` or `__attribute__((noreturn))`.
+    pub fn is_noreturn(&self) -> bool {
+        self.ret_ty
+            .quals
+            .iter()
+            .any(|qual| matches!(qual, TyQual::NoReturn))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ParamDecl {
     pub storage: Option<Storage>,
@@ -131,6 +141,7 @@ impl HirCtx<'_> {
                             ty: Ty {
                                 kind: TyKind::InitializerList,
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span,
                             },
@@ -269,7 +280,7 @@ impl HirCtx<'_> {
     pub(crate) fn lower_to_func_sig(
         &mut self,
         node: Node,
-        ret_ty: Ty,
+        mut ret_ty: Ty,
     ) -> azhdaha_errors::Result<FuncSig> {
         trace!("[HIR/FuncSig] Lowering '{}'", node.kind());
 
@@ -278,6 +289,18 @@ impl HirCtx<'_> {
             hi: node.end_byte(),
         };
 
+        // GNU attributes may also come after the parameters of the function.
+        let mut cursor = node.walk();
+
+        if node.children(&mut cursor).any(|child| {
+            matches!(
+                child.kind(),
+                constants::ATTRIBUTE_SPECIFIER | constants::ATTRIBUTE_DECLARATION
+            ) && self.is_noreturn_attribute(child)
+        }) {
+            ret_ty.quals.push(TyQual::NoReturn);
+        }
+
         let mut params = vec![];
         let mut variadic_param = false;
 
@@ -296,6 +296,7 @@ impl HirCtx<'_> {
                 Ty {
                     kind: TyKind::PrimTy(PrimTyKind::Int(4)),
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 }
@@ -312,6 +313,7 @@ impl HirCtx<'_> {
                         quals: vec![],
                     },
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 }
@@ -328,6 +330,7 @@ impl HirCtx<'_> {
                 Ty {
                     kind: *kind.clone(),
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 }
@@ -391,6 +394,7 @@ impl HirCtx<'_> {
                         let ty = Ty {
                             kind: TyKind::PrimTy(PrimTyKind::Int(8)),
                             is_linear: false,
+                            is_borrowed: false,
                             quals: vec![],
                             span,
                         };
@@ -432,6 +436,7 @@ impl HirCtx<'_> {
         let max_ty = || Ty {
             kind: TyKind::PrimTy(max_ty_kind),
             is_linear: false,
+            is_borrowed: false,
             quals: vec![],
             span,
         };
@@ -456,6 +461,7 @@ impl HirCtx<'_> {
             Ty {
                 kind: TyKind::PrimTy(PrimTyKind::Int(4)),
                 is_linear: false,
+                is_borrowed: false,
                 quals: vec![],
                 span,
             }
@@ -474,6 +480,7 @@ impl HirCtx<'_> {
         let ty = Ty {
             kind: TyKind::PrimTy(PrimTyKind::Int(8)),
             is_linear: false,
+            is_borrowed: false,
             quals: vec![],
             span: expr.span,
         };
@@ -493,6 +500,7 @@ impl HirCtx<'_> {
                     quals: vec![],
                 },
                 is_linear: false,
+                is_borrowed: false,
                 quals: vec![],
                 span: expr.span,
             };
@@ -523,6 +531,7 @@ impl HirCtx<'_> {
                 quals: vec![],
             },
             is_linear: false,
+            is_borrowed: false,
             quals: vec![],
             span: expr.span,
         };
@@ -590,6 +599,7 @@ impl HirCtx<'_> {
                             Ty {
                                 kind: TyKind::PrimTy(PrimTyKind::Int(4)),
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span,
                             },
@@ -599,6 +609,7 @@ impl HirCtx<'_> {
                             Ty {
                                 kind: TyKind::PrimTy(PrimTyKind::Int(4)),
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span,
                             },
@@ -662,6 +673,7 @@ impl HirCtx<'_> {
                                         ty: Ty {
                                             kind: TyKind::PrimTy(target),
                                             is_linear: false,
+                                            is_borrowed: false,
                                             quals: vec![],
                                             span,
                                         },
@@ -732,6 +744,7 @@ impl HirCtx<'_> {
                     ty: Ty {
                         kind: TyKind::PrimTy(PrimTyKind::Int(1)),
                         is_linear: false,
+                        is_borrowed: false,
                         quals: vec![],
                         span,
                     },
@@ -887,6 +900,7 @@ impl HirCtx<'_> {
                 let ty = Ty {
                     kind: TyKind::InitializerList,
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 };
@@ -962,6 +976,7 @@ impl HirCtx<'_> {
                 let ty = Ty {
                     kind: ty,
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 };
@@ -988,6 +1003,7 @@ impl HirCtx<'_> {
                 Ty {
                     kind: TyKind::PrimTy(PrimTyKind::Int(8)),
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 },
@@ -1004,6 +1020,7 @@ impl HirCtx<'_> {
                 Ty {
                     kind: TyKind::PrimTy(PrimTyKind::Void),
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span,
                 },
@@ -1027,6 +1044,7 @@ impl HirCtx<'_> {
                             quals: vec![],
                         },
                         is_linear: false,
+                        is_borrowed: false,
                         quals: vec![],
                         span,
                     },
@@ -1048,6 +1066,7 @@ impl HirCtx<'_> {
                     Ty {
                         kind,
                         is_linear: false,
+                        is_borrowed: false,
                         quals: vec![],
                         span,
                     },
@@ -204,6 +204,7 @@ impl<'hir> HirCtx<'hir> {
                             Ty {
                                 kind: expected_ty.clone(),
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span: expr.span,
                             },
@@ -71,6 +71,15 @@ impl HirCtx<'_> {
         };
 
         Ok(match node.kind() {
+            constants::FUNCTION_DEFINITION if self.skipped_func_defs.contains(&span) => {
+                let func_decl = self.lower_to_func_decl(node)?;
+
+                let symbol = self
+                    .symbol_resolver
+                    .insert_symbol(func_decl.ident.name.clone(), SymbolKind::Func(func_decl));
+
+                ItemKind::Decl(vec![symbol])
+            }
             constants::FUNCTION_DEFINITION => {
                 ItemKind::Func(Box::new(self.lower_to_func_def(node)?))
             }
@@ -2,9 +2,12 @@
 //! abstract syntax tree (AST) that is generated after parsing, macro expansion, and name resolution.
 //!
 
+use std::collections::HashSet;
+
 use tree_sitter::Node;
 
-use ast_utils::AstRepr;
+use ast_utils::{AstRepr, SourceInfo};
+use azhdaha_errors::{Diagnostic, Stage};
 
 /// Contains constant identifiers used to generate the HIR.
 pub(crate) mod constants;
@@ -56,7 +59,12 @@ pub struct HirCtx<'hir> {
     pub return_ty: Option<Ty>,
 
     pub root: Node<'hir>,
+    pub source_info: &'hir SourceInfo,
     pub source_code: &'hir [u8],
+
+    /// The spans of the function definitions whose bodies are not lowered, since they are
+    /// not needed, such as the unchanged functions of an edited source code.
+    pub skipped_func_defs: HashSet<Span>,
 }
 
 /// A resolver containing intrinsic functions used by GNU's libc.
@@ -67,6 +75,7 @@ fn default_symbol_resolver() -> Resolver<SymbolKind> {
         SymbolKind::TyDef(Ty {
             kind: TyKind::VaList,
             is_linear: false,
+            is_borrowed: false,
             quals: vec![],
             span: Span::DUMMY,
         }),
@@ -85,6 +94,7 @@ fn default_symbol_resolver() -> Resolver<SymbolKind> {
                     quals: vec![],
                 },
                 is_linear: false,
+                is_borrowed: false,
                 quals: vec![],
                 span: Span::DUMMY,
             },
@@ -113,6 +123,7 @@ fn default_symbol_resolver() -> Resolver<SymbolKind> {
                             ret_ty: Ty {
                                 kind: TyKind::PrimTy(PrimTyKind::Int(size)),
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span: Span::DUMMY,
                             },
@@ -122,6 +133,7 @@ fn default_symbol_resolver() -> Resolver<SymbolKind> {
                                 ty: Ty {
                                     kind: TyKind::PrimTy(PrimTyKind::Int(size)),
                                     is_linear: false,
+                                    is_borrowed: false,
                                     quals: vec![],
                                     span: Span::DUMMY,
                                 },
@@ -132,6 +144,7 @@ fn default_symbol_resolver() -> Resolver<SymbolKind> {
                         }),
                     },
                     is_linear: false,
+                    is_borrowed: false,
                     quals: vec![],
                     span: Span::DUMMY,
                 },
@@ -159,22 +172,32 @@ impl<'hir> HirCtx<'hir> {
             return_ty: None,
 
             root: ast_repr.tree.root_node(),
+            source_info: &ast_repr.source_info,
             source_code: &ast_repr.source_info.code,
+
+            skipped_func_defs: HashSet::new(),
         }
     }
 
-    /// # Panics
-    /// This function panics if the source is not utf8.
+    /// Lowers only the declarations of the functions which are defined at the spans, so they
+    /// are lowered to [`ItemKind::Decl`] rather than [`ItemKind::Func`].
+    pub fn skip_func_defs(mut self, spans: HashSet<Span>) -> Self {
+        self.skipped_func_defs = spans;
+        self
+    }
+
+    /// Lowers the items of the translation unit, along with the diagnostics of the items
+    /// which failed to be lowered.
     pub fn lower_to_hir(
         mut self,
     ) -> (
         Vec<Item>,
         Resolver<SymbolKind>,
         Resolver<CompoundTypeData>,
-        u32,
+        Vec<Diagnostic>,
     ) {
         let mut cursor = self.root.walk();
-        let mut error_count = 0;
+        let mut diagnostics = vec![];
 
         for child in self.root.children(&mut cursor) {
             match self.lower_to_item(child) {
@@ -182,8 +205,7 @@ impl<'hir> HirCtx<'hir> {
                     self.items.push(item);
                 }
                 Err(error) => {
-                    error_count += 1;
-                    error.report(std::str::from_utf8(self.source_code).unwrap());
+                    diagnostics.push(error.to_diagnostic(Stage::Hir, self.source_info));
                 }
             }
         }
@@ -192,7 +214,7 @@ impl<'hir> HirCtx<'hir> {
             self.items,
             self.symbol_resolver,
             self.type_tag_resolver,
-            error_count,
+            diagnostics,
         )
     }
 }
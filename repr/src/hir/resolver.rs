@@ -42,6 +42,7 @@ impl SymbolKind {
                     sig: Box::new(func_decl.sig.clone()),
                 },
                 is_linear: false,
+                is_borrowed: false,
                 quals: vec![],
                 span: func_decl.span,
             },
@@ -52,6 +53,7 @@ impl SymbolKind {
             &SymbolKind::EnumVariant { value: _, span } => Ty {
                 kind: TyKind::PrimTy(PrimTyKind::Int(4)),
                 is_linear: false,
+                is_borrowed: false,
                 quals: vec![],
                 span,
             },
@@ -185,6 +185,7 @@ impl HirCtx<'_> {
                                 ty: Ty {
                                     kind: TyKind::PrimTy(PrimTyKind::Bool),
                                     is_linear: false,
+                                    is_borrowed: false,
                                     quals: vec![],
                                     span,
                                 },
@@ -398,6 +399,7 @@ impl HirCtx<'_> {
                         ty: Ty {
                             kind: TyKind::PrimTy(PrimTyKind::Bool),
                             is_linear: false,
+                            is_borrowed: false,
                             quals: vec![],
                             span,
                         },
@@ -412,6 +414,7 @@ impl HirCtx<'_> {
                         ty: Ty {
                             kind: TyKind::PrimTy(PrimTyKind::Void),
                             is_linear: false,
+                            is_borrowed: false,
                             quals: vec![],
                             span,
                         },
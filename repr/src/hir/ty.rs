@@ -17,6 +17,7 @@ use super::constants;
 pub struct Ty {
     pub kind: TyKind,
     pub is_linear: bool,
+    pub is_borrowed: bool,
     pub quals: Vec<TyQual>,
     pub span: Span,
 }
@@ -94,6 +95,37 @@ impl TyKind {
         })
     }
 
+    /// Returns the index paths of the linear fields which are stored directly in a value of
+    /// this type, including the ones inside its nested compound fields.
+    pub fn linear_fields(&self, type_tag_resolver: &Resolver<CompoundTypeData>) -> Vec<Vec<usize>> {
+        let fields = match self {
+            TyKind::Struct(idx) | TyKind::Union(idx) => {
+                match type_tag_resolver.get_data_by_res(idx) {
+                    CompoundTypeData::Struct { fields } | CompoundTypeData::Union { fields } => {
+                        fields
+                    }
+                    CompoundTypeData::Enum | CompoundTypeData::DeclaredOnly => return vec![],
+                }
+            }
+            _ => return vec![],
+        };
+
+        let mut result = vec![];
+
+        for (index, field_ty) in fields.by_index.iter().enumerate() {
+            if field_ty.is_linear {
+                result.push(vec![index]);
+            }
+
+            for mut inner_path in field_ty.kind.linear_fields(type_tag_resolver) {
+                inner_path.insert(0, index);
+                result.push(inner_path);
+            }
+        }
+
+        result
+    }
+
     fn evaluate_size(&self) -> usize {
         match self {
             TyKind::PrimTy(prim_ty_kind) => match prim_ty_kind {
@@ -156,6 +188,7 @@ impl HirCtx<'_> {
         };
 
         let mut is_linear = false;
+        let mut is_borrowed = false;
         let mut quals = vec![];
 
         let mut cursor = node.walk();
@@ -166,6 +199,16 @@ impl HirCtx<'_> {
                     quals.push(self.lower_to_ty_qual(child.child(0).unwrap())?)
                 }
                 constants::LINEAR_TY_SPECIFIER => is_linear = true,
+                constants::ATTRIBUTE_SPECIFIER | constants::ATTRIBUTE_DECLARATION
+                    if self.is_borrowed_attribute(child) =>
+                {
+                    is_borrowed = true
+                }
+                constants::ATTRIBUTE_SPECIFIER | constants::ATTRIBUTE_DECLARATION
+                    if self.is_noreturn_attribute(child) =>
+                {
+                    quals.push(TyQual::NoReturn)
+                }
                 _ => (),
             }
         }
@@ -176,6 +219,7 @@ impl HirCtx<'_> {
             return Ok(Ty {
                 kind,
                 is_linear,
+                is_borrowed,
                 quals,
                 span,
             });
@@ -185,6 +229,7 @@ impl HirCtx<'_> {
             return Ok(Ty {
                 kind,
                 is_linear,
+                is_borrowed,
                 quals,
                 span,
             });
@@ -203,6 +248,7 @@ impl HirCtx<'_> {
             return Ok(Ty {
                 kind,
                 is_linear,
+                is_borrowed,
                 quals,
                 span,
             });
@@ -213,6 +259,7 @@ impl HirCtx<'_> {
             Ty {
                 kind,
                 is_linear,
+                is_borrowed,
                 quals,
                 span,
             },
@@ -223,6 +270,7 @@ impl HirCtx<'_> {
         };
 
         is_linear = false;
+        is_borrowed = false;
         quals = vec![];
 
         while let Some(node) = decl_node.child_by_field_name("declarator") {
@@ -267,6 +315,7 @@ impl HirCtx<'_> {
         Ok(Ty {
             kind,
             is_linear,
+            is_borrowed,
             quals,
             span,
         })
@@ -690,9 +739,59 @@ impl HirCtx<'_> {
             constants::VOLATILE => TyQual::Volatile,
             constants::RESTRICT => TyQual::Restrict,
             constants::ATOMIC => TyQual::Atomic,
-            constants::NORETURN => TyQual::NoReturn,
+            constants::NORETURN | constants::C11_NORETURN => TyQual::NoReturn,
             constants::EXTENSION => TyQual::Extension,
             kind => bail!(span, "Cannot lower '{kind}' to 'TyQual'."),
         })
     }
+
+    /// Whether the node is an attribute which names `noreturn` exactly, such as
+    /// `__attribute__((noreturn))`, `__attribute__((__noreturn__))` or `[[noreturn]]`.
+    pub(crate) fn is_noreturn_attribute(&self, node: Node) -> bool {
+        self.attribute_names(node).iter().any(|name| {
+            matches!(
+                *name,
+                constants::NORETURN | constants::GNU_NORETURN | constants::C11_NORETURN
+            )
+        })
+    }
+
+    /// Whether the node is the attribute which `LINEAR_BORROW` expands to, which is
+    /// `__attribute__((borrowed_type))`.
+    pub(crate) fn is_borrowed_attribute(&self, node: Node) -> bool {
+        self.attribute_names(node)
+            .contains(&constants::BORROWED_ATTRIBUTE)
+    }
+
+    /// Returns the names of the attributes which the node specifies, where the arguments of
+    /// a GNU attribute which take arguments themselves, such as `annotate("noreturn")`, are
+    /// not identifiers and are left out.
+    fn attribute_names(&self, node: Node) -> Vec<&str> {
+        let mut cursor = node.walk();
+
+        let names: Vec<Node> = match node.kind() {
+            constants::ATTRIBUTE_SPECIFIER => node
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == constants::ARGUMENT_LIST)
+                .flat_map(|arguments| {
+                    let mut cursor = arguments.walk();
+
+                    arguments
+                        .named_children(&mut cursor)
+                        .filter(|argument| argument.kind() == constants::IDENTIFIER)
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            constants::ATTRIBUTE_DECLARATION => node
+                .named_children(&mut cursor)
+                .filter_map(|attribute| attribute.child_by_field_name("name"))
+                .collect(),
+            _ => vec![],
+        };
+
+        names
+            .iter()
+            .filter_map(|name| name.utf8_text(self.source_code).ok())
+            .collect()
+    }
 }
@@ -28,7 +28,18 @@ impl<'mir> MirCtx<'mir> {
                     self.retrieve_bb(*bb).statements.push(Statement {
                         kind: StatementKind::Call(operand, operands),
                         span,
-                    })
+                    });
+
+                    // A call to a function which never returns ends the path, so the
+                    // statements after it are placed in an unreachable basic block.
+                    if is_noreturn_call(expr) {
+                        self.retrieve_bb(*bb).terminator = Some(Terminator {
+                            kind: TerminatorKind::Unreachable,
+                            span,
+                        });
+
+                        bb.set(self.alloc_bb());
+                    }
                 }
             }
             hir::StmtKind::Decl(symbols) => {
@@ -200,3 +211,18 @@ impl<'mir> MirCtx<'mir> {
         }
     }
 }
+
+/// Whether the expression calls a function which is declared as noreturn.
+fn is_noreturn_call(expr: &hir::Expr) -> bool {
+    let hir::ExprKind::Call(callee, _) = &expr.kind else {
+        return false;
+    };
+
+    match &callee.ty.kind {
+        hir::TyKind::Func { sig } => sig.is_noreturn(),
+        hir::TyKind::Ptr { kind, .. } => {
+            matches!(kind.as_ref(), hir::TyKind::Func { sig } if sig.is_noreturn())
+        }
+        _ => false,
+    }
+}
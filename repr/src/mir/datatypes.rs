@@ -101,6 +101,10 @@ impl LocalDecl {
     pub fn is_linear(&self) -> bool {
         self.ty.is_linear
     }
+
+    pub fn is_borrowed(&self) -> bool {
+        self.ty.is_borrowed
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -158,6 +162,8 @@ pub enum TerminatorKind {
         targets: [BasicBlock; 2],
     },
     Return,
+    /// The path ends in here by calling a function which never returns.
+    Unreachable,
 }
 
 #[derive(Debug, Clone)]
@@ -62,7 +62,7 @@ impl Body<'_> {
                         mark_used(targets[0]);
                         mark_used(targets[1]);
                     }
-                    TerminatorKind::Return => (),
+                    TerminatorKind::Return | TerminatorKind::Unreachable => (),
                 }
             }
         }
@@ -82,7 +82,7 @@ impl Body<'_> {
                         targets[0] = bb_map[&targets[0]];
                         targets[1] = bb_map[&targets[1]];
                     }
-                    TerminatorKind::Return => (),
+                    TerminatorKind::Return | TerminatorKind::Unreachable => (),
                 }
             }
         }
@@ -33,6 +33,7 @@ impl<'mir> MirCtx<'mir> {
                                 quals: vec![],
                             },
                             is_linear: false,
+                            is_borrowed: false,
                             quals: vec![],
                             span,
                         },
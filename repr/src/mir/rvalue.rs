@@ -54,6 +54,7 @@ impl<'mir> MirCtx<'mir> {
                             hir::Ty {
                                 kind: hir::TyKind::PrimTy(hir::PrimTyKind::Bool),
                                 is_linear: false,
+                                is_borrowed: false,
                                 quals: vec![],
                                 span,
                             },
@@ -1,15 +1,22 @@
 import subprocess
 
-# Run the safe tests
 
-output_safe = (
-    subprocess.run(
-        ["just", "run", "./tests/compile_commands_safe.json", "--do-not-report"],
-        capture_output=True,
-        text=True,
+def run(compile_commands, *args):
+    return (
+        subprocess.run(
+            ["just", "run", compile_commands, "--do-not-report", *args],
+            capture_output=True,
+            text=True,
+        )
+        .stdout.strip()
+        .split("\n")
     )
-    .stdout.strip()
-    .split("\n")
+
+
+# Run the safe tests, along with the ones which span multiple translation units
+
+output_safe = run("./tests/compile_commands_safe.json") + run(
+    "./tests/compile_commands_safe_whole_program.json", "--whole-program"
 )
 
 problematic_safe = list(filter(lambda entry: "problematic" in entry, output_safe))
@@ -18,16 +25,10 @@ false_positives = len(problematic_safe)
 successful_safe = list(filter(lambda entry: "successfully" in entry, output_safe))
 true_negative = len(successful_safe)
 
-# Run the unsafe tests
+# Run the unsafe tests, along with the ones which span multiple translation units
 
-output_unsafe = (
-    subprocess.run(
-        ["just", "run", "./tests/compile_commands_unsafe.json", "--do-not-report"],
-        capture_output=True,
-        text=True,
-    )
-    .stdout.strip()
-    .split("\n")
+output_unsafe = run("./tests/compile_commands_unsafe.json") + run(
+    "./tests/compile_commands_unsafe_whole_program.json", "--whole-program"
 )
 
 problematic_unsafe = list(filter(lambda entry: "problematic" in entry, output_unsafe))
@@ -0,0 +1,40 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+struct text_buffer {
+    LINEAR_TYPE char *data;
+    int length;
+};
+
+struct list_node {
+    LINEAR_TYPE struct list_node *next;
+    int value;
+};
+
+void fill_text_buffer(struct text_buffer *buffer) {
+    for (int i = 0; i < buffer->length - 1; i++) {
+        buffer->data[i] = 'a' + (i % 26);
+    }
+    buffer->data[buffer->length - 1] = '\0';
+}
+
+void release_list(LINEAR_TYPE struct list_node *head) {
+    free(head->next);
+    free(head);
+}
+
+int main() {
+    struct text_buffer buffer;
+    buffer.length = 32;
+    buffer.data = malloc(buffer.length);
+    fill_text_buffer(&buffer);
+    free(buffer.data);
+
+    LINEAR_TYPE struct list_node *head = malloc(sizeof(struct list_node));
+    head->value = 1;
+    head->next = malloc(sizeof(struct list_node));
+    head->next->value = 2;
+    release_list(head);
+
+    return 0;
+}
@@ -0,0 +1,25 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+struct byte_buffer {
+    LINEAR_TYPE char *data;
+    int length;
+};
+
+struct byte_buffer create_byte_buffer(int length) {
+    struct byte_buffer buffer = {malloc(length), length};
+    return buffer;
+}
+
+void release_byte_buffer(struct byte_buffer buffer) {
+    free(buffer.data);
+}
+
+int main() {
+    struct byte_buffer buffer = create_byte_buffer(64);
+    for (int i = 0; i < buffer.length; i++) {
+        buffer.data[i] = 0;
+    }
+    release_byte_buffer(buffer);
+    return 0;
+}
@@ -0,0 +1,32 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+int count_vowels(LINEAR_BORROW const char *text) {
+    int count = 0;
+    for (int i = 0; text[i] != '\0'; i++) {
+        char c = text[i];
+        if (c == 'a' || c == 'e' || c == 'i' || c == 'o' || c == 'u') {
+            count++;
+        }
+    }
+    return count;
+}
+
+int main() {
+    LINEAR_TYPE char *text = malloc(16);
+    for (int i = 0; i < 15; i++) {
+        text[i] = 'a' + i;
+    }
+    text[15] = '\0';
+
+    int vowels = count_vowels(text);
+
+    LINEAR_TYPE FILE *file = fopen("vowels.txt", "w");
+    fprintf(file, "%s has %d vowels\n", text, vowels);
+    fflush(file);
+    fclose(file);
+
+    free(text);
+    return 0;
+}
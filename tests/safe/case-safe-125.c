@@ -0,0 +1,30 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+_Noreturn void fatal(const char *message) {
+    fprintf(stderr, "fatal: %s\n", message);
+    exit(1);
+}
+
+int main() {
+    LINEAR_TYPE FILE *log = fopen("app.log", "w");
+    if (log == NULL) {
+        fatal("cannot open the log");
+    }
+
+    LINEAR_TYPE int *counters = malloc(8 * sizeof(int));
+    if (counters == NULL) {
+        fclose(log);
+        abort();
+    }
+
+    for (int i = 0; i < 8; i++) {
+        counters[i] = i;
+    }
+    fprintf(log, "%d\n", counters[7]);
+
+    free(counters);
+    fclose(log);
+    return 0;
+}
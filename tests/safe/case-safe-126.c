@@ -0,0 +1,50 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+#include <string.h>
+
+struct node {
+    int value;
+    struct node *next;
+};
+
+struct node *head = NULL;
+
+char *copy_name(const char *name) {
+    char *copy = malloc(strlen(name) + 1);
+    if (copy != NULL) {
+        strcpy(copy, name);
+    }
+    return copy;
+}
+
+void release_name(char *name) {
+    free(name);
+}
+
+int name_length(char *name) {
+    return strlen(name);
+}
+
+void push(struct node *node) {
+    node->next = head;
+    head = node;
+}
+
+int main() {
+    LINEAR_TYPE char *name = copy_name("azhdaha");
+    if (name == NULL) {
+        return 1;
+    }
+
+    int length = name_length(name);
+    release_name(name);
+
+    LINEAR_TYPE struct node *node = malloc(sizeof(struct node));
+    if (node == NULL) {
+        return 1;
+    }
+    node->value = length;
+    push(node);
+
+    return 0;
+}
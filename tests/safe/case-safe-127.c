@@ -0,0 +1,31 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+struct config {
+    int verbose;
+    int retries;
+};
+
+// azhdaha-allow(leak): the configuration lives until the program exits.
+struct config *load_config(void) {
+    LINEAR_TYPE struct config *config = malloc(sizeof(struct config));
+    if (config == NULL) {
+        abort();
+    }
+
+    config->verbose = 0;
+    config->retries = 3;
+
+    return NULL;
+}
+
+int main() {
+    LINEAR_TYPE int *counter = malloc(sizeof(int)); // azhdaha-allow(AZ0001): intentional.
+    if (counter == NULL) {
+        return 1;
+    }
+
+    *counter = load_config() == NULL;
+
+    return 0;
+}
@@ -0,0 +1,24 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+int sum_bytes(LINEAR_BORROW char *buffer, int size) {
+    int sum = 0;
+    for (int i = 0; i < size; i++) {
+        sum += buffer[i];
+    }
+    return sum;
+}
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(8);
+    for (int i = 0; i < 8; i++) {
+        buffer[i] = i;
+    }
+
+    // The buffer is only borrowed, so it is still owned after the calls.
+    int first = sum_bytes(buffer, 8);
+    int second = sum_bytes(buffer, 8);
+
+    free(buffer);
+    return first - second;
+}
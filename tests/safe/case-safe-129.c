@@ -0,0 +1,23 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+[[noreturn]] void fail(int code) {
+    exit(code);
+}
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(8);
+
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    buffer[0] = 'a';
+    if (buffer[0] != 'a') {
+        free(buffer);
+        fail(1);
+    }
+
+    free(buffer);
+    return 0;
+}
@@ -0,0 +1,38 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+// Walks over the passed buffer, which is still owned by the caller.
+char *skip_spaces(char *text) {
+    while (*text == ' ') {
+        text++;
+    }
+    return text;
+}
+
+// Returns a pointer into the passed buffer, such as `strchr`.
+char *find_char(char *text, char c) {
+    char *cursor = text;
+    while (*cursor != '\0' && *cursor != c) {
+        cursor = cursor + 1;
+    }
+    return cursor;
+}
+
+int main() {
+    LINEAR_TYPE char *text = malloc(16);
+    if (text == NULL) {
+        return 1;
+    }
+
+    for (int i = 0; i < 15; i++) {
+        text[i] = i < 3 ? ' ' : 'a' + i;
+    }
+    text[15] = '\0';
+
+    char *word = skip_spaces(text);
+    char *end = find_char(word, 'k');
+    int length = end - word;
+
+    free(text);
+    return length;
+}
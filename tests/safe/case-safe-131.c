@@ -0,0 +1,13 @@
+#include "case-safe-131.h"
+
+int main() {
+    LINEAR_TYPE int *counter = malloc(sizeof(int));
+    if (counter == NULL) {
+        return 1;
+    }
+
+    *counter = 0;
+    free(counter);
+
+    return 0;
+}
@@ -0,0 +1,7 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+// azhdaha-allow(leak): the buffer of the log lives until the program exits.
+char *open_log(void);
+
+void write_log(char *log, const char *message);
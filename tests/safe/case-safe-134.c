@@ -0,0 +1,23 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+static char *last_checked;
+
+int check_header(LINEAR_BORROW char *buffer) {
+    // The buffer is only remembered for the diagnostics, so its caller still owns it.
+    last_checked = buffer;
+    return buffer[0] == 'H';
+}
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(8);
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    buffer[0] = 'H';
+    int valid = check_header(buffer);
+
+    free(buffer);
+    return valid ? 0 : 1;
+}
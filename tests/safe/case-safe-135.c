@@ -0,0 +1,32 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int main(int argc, char **argv) {
+    LINEAR_TYPE char *buffer = malloc(16);
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    size_t capacity = 16;
+
+    for (int i = 1; i < argc; i++) {
+        if (strlen(argv[i]) >= capacity) {
+            // The buffer is replaced on this path, so it is owned on both of the paths
+            // which merge at the end of the iteration.
+            capacity = strlen(argv[i]) + 1;
+            free(buffer);
+            buffer = malloc(capacity);
+            if (buffer == NULL) {
+                return 1;
+            }
+        }
+
+        strcpy(buffer, argv[i]);
+        puts(buffer);
+    }
+
+    free(buffer);
+    return 0;
+}
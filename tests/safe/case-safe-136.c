@@ -0,0 +1,17 @@
+#include <azhdaha.h>
+#include <stdio.h>
+
+#include "case-safe-136.h"
+
+// The message is handed to 'message_free' which is defined in another translation unit
+// and agrees with the declaration on the linearity of its parameter.
+int main() {
+    LINEAR_TYPE char *message = message_new(16);
+
+    snprintf(message, 16, "hello");
+    puts(message);
+
+    message_free(message);
+
+    return 0;
+}
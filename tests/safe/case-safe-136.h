@@ -0,0 +1,6 @@
+#include <azhdaha.h>
+#include <stddef.h>
+
+LINEAR_TYPE char *message_new(size_t size);
+
+void message_free(LINEAR_TYPE char *message);
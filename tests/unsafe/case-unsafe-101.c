@@ -0,0 +1,22 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+struct text_buffer {
+    LINEAR_TYPE char *data;
+    int length;
+};
+
+void fill_text_buffer(struct text_buffer *buffer) {
+    for (int i = 0; i < buffer->length - 1; i++) {
+        buffer->data[i] = 'a' + (i % 26);
+    }
+    buffer->data[buffer->length - 1] = '\0';
+}
+
+int main() {
+    struct text_buffer buffer;
+    buffer.length = 32;
+    buffer.data = malloc(buffer.length);
+    fill_text_buffer(&buffer);
+    return 0; // Memory leak of buffer.data
+}
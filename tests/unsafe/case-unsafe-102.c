@@ -0,0 +1,16 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+struct packet {
+    LINEAR_TYPE char *payload;
+    int size;
+};
+
+int main() {
+    LINEAR_TYPE struct packet *packet = malloc(sizeof(struct packet));
+    packet->size = 32;
+    packet->payload = malloc(packet->size);
+    packet->payload[0] = 'x';
+    free(packet); // Memory leak of packet->payload
+    return 0;
+}
@@ -0,0 +1,30 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+int count_vowels(LINEAR_BORROW const char *text) {
+    int count = 0;
+    for (int i = 0; text[i] != '\0'; i++) {
+        char c = text[i];
+        if (c == 'a' || c == 'e' || c == 'i' || c == 'o' || c == 'u') {
+            count++;
+        }
+    }
+    return count;
+}
+
+int main() {
+    LINEAR_TYPE char *text = malloc(16);
+    for (int i = 0; i < 15; i++) {
+        text[i] = 'a' + i;
+    }
+    text[15] = '\0';
+
+    free(text);
+    int vowels = count_vowels(text); // Use after free
+
+    LINEAR_TYPE FILE *file = fopen("vowels.txt", "w");
+    fprintf(file, "%d vowels\n", vowels);
+    fclose(file);
+    return 0;
+}
@@ -0,0 +1,27 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+int copy_first_line(const char *path) {
+    LINEAR_TYPE FILE *file = fopen(path, "r");
+    if (file == NULL) {
+        return -1;
+    }
+
+    LINEAR_TYPE char *line = malloc(128);
+    if (line == NULL) {
+        return -1; // Memory leak of file
+    }
+
+    if (fgets(line, 128, file) != NULL) {
+        printf("%s", line);
+    }
+
+    free(line);
+    fclose(file);
+    return 0;
+}
+
+int main() {
+    return copy_first_line("input.txt");
+}
@@ -0,0 +1,26 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+void fatal(const char *message) __attribute__((noreturn));
+
+int main() {
+    LINEAR_TYPE FILE *log = fopen("app.log", "w");
+    if (log == NULL) {
+        fatal("cannot open the log");
+    }
+
+    LINEAR_TYPE int *counters = malloc(8 * sizeof(int));
+    if (counters == NULL) {
+        exit(1); // Memory leak of log
+    }
+
+    for (int i = 0; i < 8; i++) {
+        counters[i] = i;
+    }
+    fprintf(log, "%d\n", counters[7]);
+
+    free(counters);
+    fclose(log);
+    return 0;
+}
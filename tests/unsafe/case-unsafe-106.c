@@ -0,0 +1,28 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+#include <string.h>
+
+char *copy_name(const char *name) {
+    char *copy = malloc(strlen(name) + 1);
+    if (copy != NULL) {
+        strcpy(copy, name);
+    }
+    return copy;
+}
+
+void release_name(char *name) {
+    free(name);
+}
+
+int main() {
+    LINEAR_TYPE char *name = copy_name("azhdaha");
+    if (name == NULL) {
+        return 1;
+    }
+
+    release_name(name);
+
+    name[0] = 'A'; // Use after free of name
+
+    return 0;
+}
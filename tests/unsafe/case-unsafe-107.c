@@ -0,0 +1,14 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+int main() {
+    LINEAR_TYPE int *ptr = malloc(sizeof(int));
+    if (ptr == NULL) {
+        return 1;
+    }
+
+    // azhdaha-allow(leak): the value is freed below.
+    free(ptr);
+
+    return 0;
+}
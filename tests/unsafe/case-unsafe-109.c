@@ -0,0 +1,24 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+// The attribute only mentions noreturn in its argument, so the function returns.
+__attribute__((annotate("maybe_noreturn"))) void report_failure(int code) {
+    (void)code;
+}
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(8);
+
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    buffer[0] = 'a';
+    if (buffer[0] != 'a') {
+        free(buffer);
+        report_failure(1);
+    }
+
+    free(buffer); // Double free
+    return 0;
+}
@@ -0,0 +1,24 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+typedef int score;
+
+int last_score(int first, int second) {
+    return (score)(first, second);
+}
+
+LINEAR_TYPE int *allocate_scores(int count) {
+    LINEAR_TYPE int *scores = malloc(count * sizeof(int));
+    return scores;
+}
+
+int main() {
+    LINEAR_TYPE int *scores = allocate_scores(4);
+    if (scores == NULL) {
+        return 1;
+    }
+
+    scores[0] = last_score(1, 2);
+
+    return 0;
+}
@@ -0,0 +1,25 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+void release_buffer(LINEAR_TYPE char *buffer) {
+    free(buffer);
+}
+
+void finish_buffer(LINEAR_TYPE char *buffer, int length) {
+    if (length > 0) {
+        buffer[length - 1] = '\0';
+    }
+    release_buffer(buffer);
+}
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(16);
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    finish_buffer(buffer, 16);
+    buffer[0] = 'a';
+
+    return 0;
+}
@@ -0,0 +1,19 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+// The checksum is computed by a library whose source is not available.
+unsigned checksum(LINEAR_BORROW const char *buffer, int size);
+
+int main() {
+    LINEAR_TYPE char *buffer = malloc(8);
+    if (buffer == NULL) {
+        return 1;
+    }
+
+    for (int i = 0; i < 8; i++) {
+        buffer[i] = i;
+    }
+
+    free(buffer);
+    return checksum(buffer, 8) == 0; // Use after free
+}
@@ -0,0 +1,23 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+#include <string.h>
+
+int main() {
+    LINEAR_TYPE char *name = malloc(16);
+    if (name == NULL) {
+        return 1;
+    }
+
+    LINEAR_TYPE char *path = malloc(64);
+    if (path == NULL) {
+        free(name);
+        return 1;
+    }
+
+    strcpy(name, "config");
+    free(name);
+    name[0] = 'C'; // Use after free
+
+    strcpy(path, "/etc/config");
+    return 0; // Leak of path
+}
@@ -0,0 +1,21 @@
+#include <azhdaha.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+int main(int argc, char **argv) {
+    LINEAR_TYPE char *line = malloc(32);
+    if (line == NULL) {
+        return 1;
+    }
+
+    snprintf(line, 32, "%d arguments", argc);
+
+    if (argc > 1) {
+        puts(line);
+        free(line);
+    }
+
+    // The line is freed only on one of the paths which merge in here.
+    free(line); // Double free
+    return 0;
+}
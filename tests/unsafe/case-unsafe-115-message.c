@@ -0,0 +1,16 @@
+#include <azhdaha.h>
+#include <stdlib.h>
+
+#include "case-unsafe-115.h"
+
+LINEAR_TYPE char *message_new(size_t size) {
+    LINEAR_TYPE char *message = malloc(size);
+
+    return message;
+}
+
+void message_free(LINEAR_TYPE char *message) {
+    free(message);
+
+    return;
+}
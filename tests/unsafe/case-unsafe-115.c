@@ -0,0 +1,15 @@
+#include <azhdaha.h>
+#include <stdio.h>
+
+#include "case-unsafe-115.h"
+
+int main() {
+    LINEAR_TYPE char *message = message_new(16);
+
+    snprintf(message, 16, "hello");
+    puts(message);
+
+    message_free(message);
+
+    return 0;
+}
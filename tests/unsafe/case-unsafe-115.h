@@ -0,0 +1,7 @@
+#include <azhdaha.h>
+#include <stddef.h>
+
+LINEAR_TYPE char *message_new(size_t size);
+
+// The parameter is not annotated while it is linear in the definition.
+void message_free(char *message); // Mismatched declaration